@@ -0,0 +1,353 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use axum::body::Body;
+use axum::extract::{Path, Query, State as Extract};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+
+use crate::components::name::ComponentName;
+use crate::components::output::NamedOutput;
+use crate::topology::{ComponentHandle, Config, Instance};
+
+#[derive(Debug, serde::Serialize)]
+struct StatusResponse {
+    alive: bool,
+    ready: bool,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+struct TopologyComponent {
+    name: String,
+    kind: &'static str,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+struct TopologyEdge {
+    from: String,
+    output: String,
+    to: String,
+}
+
+#[derive(Clone, Debug, Default, serde::Serialize)]
+struct TopologyResponse {
+    components: Vec<TopologyComponent>,
+    edges: Vec<TopologyEdge>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct ComponentResponse {
+    name: String,
+    kind: &'static str,
+    running: bool,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+struct TapParams {
+    /// Only keeps one event out of `sample_rate`
+    sample_rate: Option<u64>,
+    /// Closes the stream after this number of events
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+struct Snapshot {
+    ready: bool,
+    topology: TopologyResponse,
+    components: HashMap<ComponentName, ComponentHandle>,
+    config: Config,
+}
+
+/// State of the running topology, as seen by the admin API.
+#[derive(Clone, Debug, Default)]
+pub struct State {
+    inner: Arc<ArcSwap<Snapshot>>,
+}
+
+impl State {
+    /// Marks the topology as ready and keeps track of its components.
+    pub fn update(&self, instance: &Instance) {
+        let config = instance.config();
+        let mut components = config
+            .components()
+            .map(|(name, kind)| TopologyComponent {
+                name: name.to_string(),
+                kind,
+            })
+            .collect::<Vec<_>>();
+        components.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut edges = config
+            .edges()
+            .map(|(input, name)| TopologyEdge {
+                from: input.name.to_string(),
+                output: input.output.to_string(),
+                to: name.to_string(),
+            })
+            .collect::<Vec<_>>();
+        edges.sort_by(|a, b| (&a.from, &a.output, &a.to).cmp(&(&b.from, &b.output, &b.to)));
+        self.inner.store(Arc::new(Snapshot {
+            ready: true,
+            topology: TopologyResponse { components, edges },
+            components: instance.components(),
+            config: instance.config().clone(),
+        }));
+    }
+
+    /// Marks the topology as not ready, when shutting down.
+    pub fn set_not_ready(&self) {
+        let current = self.inner.load();
+        self.inner.store(Arc::new(Snapshot {
+            ready: false,
+            topology: current.topology.clone(),
+            components: current.components.clone(),
+            config: current.config.clone(),
+        }));
+    }
+}
+
+async fn status(Extract(state): Extract<State>) -> Response {
+    let ready = state.inner.load().ready;
+    let code = match ready {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    (code, Json(StatusResponse { alive: true, ready })).into_response()
+}
+
+async fn topology(Extract(state): Extract<State>) -> Json<TopologyResponse> {
+    Json(state.inner.load().topology.clone())
+}
+
+async fn component(Extract(state): Extract<State>, Path(name): Path<String>) -> Response {
+    let snapshot = state.inner.load();
+    match snapshot.components.get(name.as_str()) {
+        Some(handle) => Json(ComponentResponse {
+            kind: handle.kind,
+            running: handle.is_running(),
+            name,
+        })
+        .into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn tap_default(
+    state: Extract<State>,
+    Path(name): Path<String>,
+    params: Query<TapParams>,
+) -> Response {
+    tap(
+        state,
+        Path((name, NamedOutput::Default.to_string())),
+        params,
+    )
+    .await
+}
+
+/// Streams a copy of the events leaving a component output, as JSON lines.
+///
+/// The events are dropped when the client doesn't read them fast enough, so that the
+/// delivery to the other components doesn't slow down.
+async fn tap(
+    Extract(state): Extract<State>,
+    Path((name, output)): Path<(String, String)>,
+    Query(params): Query<TapParams>,
+) -> Response {
+    let snapshot = state.inner.load();
+    let Ok(output) = output.parse::<NamedOutput>() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let taps = match snapshot.components.get(name.as_str()) {
+        Some(handle) if snapshot.config.has_output(&name, &output) => handle.taps.as_ref(),
+        _ => None,
+    };
+    let Some(taps) = taps else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let sample_rate = params.sample_rate.unwrap_or(1).max(1);
+    let mut count: u64 = 0;
+    let stream = BroadcastStream::new(taps.subscribe(&output))
+        .filter_map(|received| received.ok())
+        .filter(move |_| {
+            count += 1;
+            (count - 1).is_multiple_of(sample_rate)
+        })
+        .take(params.limit.unwrap_or(usize::MAX))
+        .map(|event| serde_json::to_string(&event).map(|line| line + "\n"));
+    (
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(stream),
+    )
+        .into_response()
+}
+
+fn router(state: State) -> Router {
+    Router::new()
+        .route("/status", get(status))
+        .route("/topology", get(topology))
+        .route("/components/:name", get(component))
+        .route("/tap/:name", get(tap_default))
+        .route("/tap/:name/:output", get(tap))
+        .with_state(state)
+}
+
+/// Starts the admin server in the background.
+pub async fn serve(
+    address: SocketAddr,
+    state: State,
+) -> std::io::Result<tokio::task::JoinHandle<()>> {
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    tracing::info!("admin api listening on {address}");
+    Ok(tokio::spawn(async move {
+        if let Err(err) = axum::serve(listener, router(state)).await {
+            tracing::error!("admin api stopped: {err:?}");
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    use super::{router, State};
+    use crate::topology::Config;
+
+    async fn call(state: &State, uri: &str) -> (StatusCode, serde_json::Value) {
+        let response = router(state.clone())
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn should_not_be_ready_before_starting() {
+        let state = State::default();
+        let (status, body) = call(&state, "/status").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body, serde_json::json!({"alive": true, "ready": false}));
+    }
+
+    #[tokio::test]
+    async fn should_describe_running_topology() {
+        let config: Config = toml::from_str(
+            r#"
+[sources.generator]
+type = "random_logs"
+
+[sinks.output]
+type = "black_hole"
+inputs = ["generator"]
+"#,
+        )
+        .unwrap();
+        let instance = config.build().await.unwrap().start().await.unwrap();
+        let state = State::default();
+        state.update(&instance);
+
+        let (status, body) = call(&state, "/status").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, serde_json::json!({"alive": true, "ready": true}));
+
+        let (_, body) = call(&state, "/topology").await;
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "components": [
+                    {"name": "generator", "kind": "source"},
+                    {"name": "output", "kind": "sink"},
+                ],
+                "edges": [{"from": "generator", "output": "default", "to": "output"}],
+            })
+        );
+
+        let (_, body) = call(&state, "/components/output").await;
+        assert_eq!(
+            body,
+            serde_json::json!({"name": "output", "kind": "sink", "running": true})
+        );
+        let (status, _) = call(&state, "/components/unknown").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        state.set_not_ready();
+        instance
+            .shutdown(tokio::time::Duration::from_secs(1))
+            .await
+            .unwrap();
+        let (status, _) = call(&state, "/status").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        let (_, body) = call(&state, "/components/output").await;
+        assert_eq!(
+            body,
+            serde_json::json!({"name": "output", "kind": "sink", "running": false})
+        );
+    }
+
+    #[tokio::test]
+    async fn should_stream_events_of_an_output() {
+        let config: Config = toml::from_str(
+            r#"
+[sources.generator]
+type = "random_logs"
+interval = 10
+
+[sinks.output]
+type = "black_hole"
+inputs = ["generator"]
+"#,
+        )
+        .unwrap();
+        let instance = config.build().await.unwrap().start().await.unwrap();
+        let state = State::default();
+        state.update(&instance);
+
+        let response = router(state.clone())
+            .oneshot(
+                Request::get("/tap/generator?limit=3&sample_rate=2")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = tokio::time::timeout(
+            tokio::time::Duration::from_secs(5),
+            axum::body::to_bytes(response.into_body(), usize::MAX),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        let lines = std::str::from_utf8(&body)
+            .unwrap()
+            .lines()
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        for line in lines {
+            assert!(serde_json::from_str::<serde_json::Value>(line).is_ok());
+        }
+
+        let (status, _) = call(&state, "/tap/generator/unknown").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = call(&state, "/tap/output").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = call(&state, "/tap/unknown").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        instance
+            .shutdown(tokio::time::Duration::from_secs(1))
+            .await
+            .unwrap();
+    }
+}
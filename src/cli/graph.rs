@@ -0,0 +1,30 @@
+use std::process::ExitCode;
+
+use crate::topology::GraphFormat;
+
+#[derive(Debug, Default, clap::Args)]
+pub struct Command {
+    /// Language used to render the graph
+    #[arg(long, value_name = "FORMAT", default_value = "dot")]
+    format: GraphFormat,
+}
+
+impl Command {
+    pub async fn execute(self, configs: &super::ConfigArgs) -> ExitCode {
+        let Some(config) = super::load_config(configs) else {
+            return ExitCode::FAILURE;
+        };
+        match config.validate() {
+            Ok(config) => {
+                print!("{}", config.to_graph(self.format));
+                ExitCode::SUCCESS
+            }
+            Err(errors) => {
+                for error in errors {
+                    eprintln!("error: {error}");
+                }
+                ExitCode::FAILURE
+            }
+        }
+    }
+}
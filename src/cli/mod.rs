@@ -0,0 +1,134 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use crate::topology::{Config, Format, LoadingError};
+
+mod graph;
+mod run;
+mod test;
+mod validate;
+
+#[derive(Debug, clap::Args)]
+struct ConfigArgs {
+    /// Path to a configuration file, a directory or a glob pattern, can be repeated to merge
+    /// several files
+    #[arg(
+        short,
+        long = "config",
+        value_name = "PATH",
+        default_value = "./example.toml",
+        global = true
+    )]
+    configs: Vec<PathBuf>,
+    /// Format of the configuration files, guessed from their extension when not specified
+    #[arg(long, value_name = "FORMAT", global = true)]
+    config_format: Option<Format>,
+}
+
+impl ConfigArgs {
+    fn load(&self) -> Result<Config, LoadingError> {
+        Config::from_paths(&self.configs, self.config_format)
+    }
+}
+
+#[derive(Debug, clap::Parser)]
+#[command(about, version)]
+pub struct Args {
+    #[command(flatten)]
+    config: ConfigArgs,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+impl Args {
+    pub async fn execute(self) -> ExitCode {
+        match self.command.unwrap_or_default() {
+            Command::Run(inner) => inner.execute(&self.config).await,
+            Command::Validate(inner) => inner.execute(&self.config).await,
+            Command::Test(inner) => inner.execute(&self.config).await,
+            Command::Graph(inner) => inner.execute(&self.config).await,
+        }
+    }
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Starts the topology, this is the default command
+    Run(self::run::Command),
+    /// Checks the configuration without starting any component
+    Validate(self::validate::Command),
+    /// Runs the tests of the configuration against its transforms
+    Test(self::test::Command),
+    /// Renders the topology as a graph, to be displayed with Graphviz or Mermaid
+    Graph(self::graph::Command),
+}
+
+impl Default for Command {
+    fn default() -> Self {
+        Self::Run(Default::default())
+    }
+}
+
+fn report_error(error: &dyn std::error::Error) {
+    eprintln!("error: {error}");
+    let mut source = error.source();
+    while let Some(cause) = source {
+        eprintln!("  caused by: {cause}");
+        source = cause.source();
+    }
+}
+
+fn load_config(config: &ConfigArgs) -> Option<Config> {
+    match config.load() {
+        Ok(config) => Some(config),
+        Err(error) => {
+            report_error(&error);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::{Args, Command};
+    use crate::topology::Format;
+
+    #[test]
+    fn should_use_default_config() {
+        let args = Args::try_parse_from(["tiny-vector"]).unwrap();
+        assert_eq!(args.config.configs.len(), 1);
+        assert!(args.config.config_format.is_none());
+        assert!(args.command.is_none());
+    }
+
+    #[test]
+    fn should_accept_multiple_configs() {
+        let args = Args::try_parse_from([
+            "tiny-vector",
+            "--config",
+            "first.toml",
+            "--config",
+            "second.toml",
+            "validate",
+        ])
+        .unwrap();
+        assert_eq!(args.config.configs.len(), 2);
+        assert!(matches!(args.command, Some(Command::Validate(_))));
+    }
+
+    #[test]
+    fn should_force_config_format() {
+        let args = Args::try_parse_from([
+            "tiny-vector",
+            "validate",
+            "--config",
+            "pipeline.conf",
+            "--config-format",
+            "yaml",
+        ])
+        .unwrap();
+        assert_eq!(args.config.config_format, Some(Format::Yaml));
+    }
+}
@@ -0,0 +1,164 @@
+use std::net::SocketAddr;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use crate::topology::{BuildError, ReloadError};
+
+const DEFAULT_SHUTDOWN_TIMEOUT: u64 = 30;
+const DEFAULT_API_ADDRESS: SocketAddr =
+    SocketAddr::new(std::net::IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED), 3000);
+
+#[derive(Debug, clap::Args)]
+pub struct Command {
+    /// Time, in seconds, given to the components to drain their events when they get stopped,
+    /// on shutdown or when reloading the configuration with SIGHUP
+    #[arg(long, value_name = "SECONDS", default_value_t = DEFAULT_SHUTDOWN_TIMEOUT)]
+    shutdown_timeout: u64,
+    /// Address the admin API listens on
+    #[arg(long, value_name = "ADDRESS", default_value_t = DEFAULT_API_ADDRESS)]
+    api_address: SocketAddr,
+    /// Disables the admin API
+    #[arg(long)]
+    no_api: bool,
+}
+
+impl Default for Command {
+    fn default() -> Self {
+        Self {
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            api_address: DEFAULT_API_ADDRESS,
+            no_api: false,
+        }
+    }
+}
+
+enum Signal {
+    Shutdown,
+    Reload,
+}
+
+#[cfg(unix)]
+struct Signals {
+    terminate: tokio::signal::unix::Signal,
+    interrupt: tokio::signal::unix::Signal,
+    hangup: tokio::signal::unix::Signal,
+}
+
+#[cfg(unix)]
+impl Signals {
+    fn new() -> std::io::Result<Self> {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        Ok(Self {
+            terminate: signal(SignalKind::terminate())?,
+            interrupt: signal(SignalKind::interrupt())?,
+            hangup: signal(SignalKind::hangup())?,
+        })
+    }
+
+    async fn next(&mut self) -> Signal {
+        tokio::select! {
+            _ = self.terminate.recv() => {
+                tracing::info!("received SIGTERM");
+                Signal::Shutdown
+            }
+            _ = self.interrupt.recv() => {
+                tracing::info!("received SIGINT");
+                Signal::Shutdown
+            }
+            _ = self.hangup.recv() => {
+                tracing::info!("received SIGHUP");
+                Signal::Reload
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct Signals;
+
+#[cfg(not(unix))]
+impl Signals {
+    fn new() -> std::io::Result<Self> {
+        Ok(Self)
+    }
+
+    async fn next(&mut self) -> Signal {
+        let _ = tokio::signal::ctrl_c().await;
+        tracing::info!("received ctrl-c");
+        Signal::Shutdown
+    }
+}
+
+impl Command {
+    pub async fn execute(self, configs: &super::ConfigArgs) -> ExitCode {
+        let Some(config) = super::load_config(configs) else {
+            return ExitCode::FAILURE;
+        };
+        let topology = match config.build().await {
+            Ok(inner) => inner,
+            Err(BuildError::Validation(errors)) => {
+                for error in errors {
+                    eprintln!("error: {error}");
+                }
+                return ExitCode::FAILURE;
+            }
+            Err(error) => {
+                super::report_error(&error);
+                return ExitCode::FAILURE;
+            }
+        };
+        let mut signals = match Signals::new() {
+            Ok(inner) => inner,
+            Err(error) => {
+                super::report_error(&error);
+                return ExitCode::FAILURE;
+            }
+        };
+        let api = crate::api::State::default();
+        if !self.no_api {
+            if let Err(error) = crate::api::serve(self.api_address, api.clone()).await {
+                super::report_error(&error);
+                return ExitCode::FAILURE;
+            }
+        }
+        let mut instance = match topology.start().await {
+            Ok(inner) => inner,
+            Err(error) => {
+                super::report_error(&error);
+                return ExitCode::FAILURE;
+            }
+        };
+        api.update(&instance);
+        let timeout = Duration::from_secs(self.shutdown_timeout);
+        while let Signal::Reload = signals.next().await {
+            tracing::info!("reloading configuration");
+            let result = match configs.load() {
+                Ok(config) => instance.reload(config, timeout).await,
+                Err(error) => {
+                    tracing::error!("unable to load configuration: {error:?}");
+                    continue;
+                }
+            };
+            match result {
+                Ok(_) => tracing::info!("configuration reloaded"),
+                Err(ReloadError::Build(BuildError::Validation(errors))) => {
+                    for error in errors {
+                        tracing::error!("invalid configuration: {error}");
+                    }
+                }
+                Err(error) => tracing::error!("unable to reload configuration: {error:?}"),
+            }
+            // a failed reload can still have restarted some components
+            api.update(&instance);
+        }
+        api.set_not_ready();
+        match instance.shutdown(timeout).await {
+            Ok(_) => ExitCode::SUCCESS,
+            Err(error) => {
+                super::report_error(&error);
+                ExitCode::FAILURE
+            }
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use std::process::ExitCode;
+
+#[derive(Debug, Default, clap::Args)]
+pub struct Command {}
+
+impl Command {
+    pub async fn execute(self, configs: &super::ConfigArgs) -> ExitCode {
+        let Some(config) = super::load_config(configs) else {
+            return ExitCode::FAILURE;
+        };
+        let config = match config.validate() {
+            Ok(config) => config,
+            Err(errors) => {
+                for error in errors {
+                    eprintln!("error: {error}");
+                }
+                return ExitCode::FAILURE;
+            }
+        };
+        if config.tests().is_empty() {
+            eprintln!("error: no test found in the configuration");
+            return ExitCode::FAILURE;
+        }
+        let mut failed = 0;
+        for test in config.tests() {
+            match test.run(&config).await {
+                Ok(_) => println!("test {:?} ... ok", test.name),
+                Err(error) => {
+                    println!("test {:?} ... failed", test.name);
+                    super::report_error(&error);
+                    failed += 1;
+                }
+            }
+        }
+        let total = config.tests().len();
+        println!("{} passed, {failed} failed", total - failed);
+        match failed {
+            0 => ExitCode::SUCCESS,
+            _ => ExitCode::FAILURE,
+        }
+    }
+}
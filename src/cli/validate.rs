@@ -0,0 +1,24 @@
+use std::process::ExitCode;
+
+#[derive(Debug, Default, clap::Args)]
+pub struct Command {}
+
+impl Command {
+    pub async fn execute(self, configs: &super::ConfigArgs) -> ExitCode {
+        let Some(config) = super::load_config(configs) else {
+            return ExitCode::FAILURE;
+        };
+        match config.validate() {
+            Ok(_) => {
+                println!("configuration is valid");
+                ExitCode::SUCCESS
+            }
+            Err(errors) => {
+                for error in errors {
+                    eprintln!("error: {error}");
+                }
+                ExitCode::FAILURE
+            }
+        }
+    }
+}
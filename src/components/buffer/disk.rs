@@ -0,0 +1,664 @@
+//! Buffer persisting the events in a queue of segment files.
+//!
+//! Each segment contains records made of the length of the encoded event, as a little
+//! endian `u32`, followed by the event encoded in JSON. The position of the last
+//! acknowledged event is stored in a checkpoint file and the segments that have been
+//! entirely acknowledged are removed.
+//!
+//! The records are flushed to the operating system when written but the segments are only
+//! synced to the disk once full, so a power loss can lose the last written events while a
+//! crash of the process can't. The events received by a component are acknowledged when it
+//! asks for the next ones, so it has to be done handling them, retrying if needed, before
+//! doing so.
+
+use std::io::{ErrorKind, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc::error::SendError;
+use tokio::sync::{Mutex, Notify};
+
+use super::WhenFull;
+use crate::event::Event;
+
+const SEGMENT_EXTENSION: &str = "segment";
+const CHECKPOINT_FILE: &str = "checkpoint";
+const MAX_SEGMENT_SIZE: u64 = 8 * 1024 * 1024;
+const RECORD_HEADER_SIZE: u64 = 4;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+struct Position {
+    segment: u64,
+    offset: u64,
+}
+
+fn segment_path(directory: &Path, segment: u64) -> PathBuf {
+    directory.join(format!("{segment:020}.{SEGMENT_EXTENSION}"))
+}
+
+fn segment_id(path: &Path) -> Option<u64> {
+    if path.extension()? != SEGMENT_EXTENSION {
+        return None;
+    }
+    path.file_stem()?.to_str()?.parse().ok()
+}
+
+async fn read_checkpoint(directory: &Path) -> std::io::Result<Option<Position>> {
+    let content = match tokio::fs::read_to_string(directory.join(CHECKPOINT_FILE)).await {
+        Ok(inner) => inner,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let position = content
+        .trim()
+        .split_once(' ')
+        .and_then(|(segment, offset)| Some((segment.parse().ok()?, offset.parse().ok()?)))
+        .map(|(segment, offset)| Position { segment, offset });
+    if position.is_none() {
+        tracing::warn!("invalid buffer checkpoint, reading from the oldest segment");
+    }
+    Ok(position)
+}
+
+async fn write_checkpoint(directory: &Path, position: Position) -> std::io::Result<()> {
+    let target = directory.join(CHECKPOINT_FILE);
+    let temporary = target.with_extension("tmp");
+    let content = format!("{} {}\n", position.segment, position.offset);
+    let mut file = tokio::fs::File::create(&temporary).await?;
+    file.write_all(content.as_bytes()).await?;
+    // persisted before replacing the previous one, so that a crash leaves either of them
+    file.sync_all().await?;
+    tokio::fs::rename(&temporary, &target).await
+}
+
+#[derive(Debug)]
+struct Writer {
+    segment: u64,
+    offset: u64,
+    file: tokio::fs::File,
+}
+
+impl Writer {
+    async fn create(directory: &Path, segment: u64) -> std::io::Result<Self> {
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(segment_path(directory, segment))
+            .await?;
+        Ok(Self {
+            segment,
+            offset: 0,
+            file,
+        })
+    }
+
+    async fn append(&mut self, shared: &Shared, encoded: &[u8]) -> std::io::Result<()> {
+        let record_size = RECORD_HEADER_SIZE + encoded.len() as u64;
+        if self.offset > 0 && self.offset + record_size > shared.segment_size {
+            self.file.flush().await?;
+            self.file.sync_all().await?;
+            *self = Self::create(&shared.directory, self.segment + 1).await?;
+        }
+        let mut record = Vec::with_capacity(record_size as usize);
+        record.extend_from_slice(&(encoded.len() as u32).to_le_bytes());
+        record.extend_from_slice(encoded);
+        self.file.write_all(&record).await?;
+        self.file.flush().await?;
+        self.offset += record_size;
+        shared.size.fetch_add(record_size, Ordering::SeqCst);
+        *shared.written.lock().unwrap() = Position {
+            segment: self.segment,
+            offset: self.offset,
+        };
+        shared.written_notify.notify_one();
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct Shared {
+    directory: PathBuf,
+    max_size: u64,
+    segment_size: u64,
+    when_full: WhenFull,
+    writer: Mutex<Writer>,
+    /// Position right after the last written event
+    written: std::sync::Mutex<Position>,
+    /// Size of the events that haven't been acknowledged yet
+    size: AtomicU64,
+    dropped: AtomicU64,
+    senders_closed: AtomicBool,
+    receiver_closed: AtomicBool,
+    /// Wakes up the receiver when an event is written or the senders are dropped
+    written_notify: Notify,
+    /// Wakes up the blocked senders when some events are acknowledged
+    acked_notify: Notify,
+}
+
+/// Opens the buffer stored in the given directory, the events that have not been
+/// acknowledged before will be received first.
+pub async fn open(
+    directory: &Path,
+    max_size: u64,
+    when_full: WhenFull,
+) -> std::io::Result<(Sender, Receiver)> {
+    tokio::fs::create_dir_all(directory).await?;
+    let mut segments = Vec::new();
+    let mut entries = tokio::fs::read_dir(directory).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        if let Some(segment) = segment_id(&entry.path()) {
+            segments.push((segment, entry.metadata().await?.len()));
+        }
+    }
+    segments.sort_unstable();
+
+    let oldest = segments.first().map(|(segment, _)| *segment).unwrap_or(0);
+    let position = match read_checkpoint(directory).await? {
+        Some(inner) => inner,
+        None => Position {
+            segment: oldest,
+            offset: 0,
+        },
+    };
+    let size = segments
+        .iter()
+        .filter(|(segment, _)| *segment >= position.segment)
+        .map(|(segment, size)| match *segment == position.segment {
+            true => size.saturating_sub(position.offset),
+            false => *size,
+        })
+        .sum();
+    // always write in a new segment so that a record partially written during a crash
+    // only ends the segment it belongs to
+    let next = segments
+        .last()
+        .map(|(segment, _)| segment + 1)
+        .unwrap_or(0)
+        .max(position.segment + 1);
+    let writer = Writer::create(directory, next).await?;
+
+    let shared = Arc::new(Shared {
+        directory: directory.to_path_buf(),
+        max_size,
+        segment_size: (max_size / 8).clamp(1, MAX_SEGMENT_SIZE),
+        when_full,
+        writer: Mutex::new(writer),
+        written: std::sync::Mutex::new(Position {
+            segment: next,
+            offset: 0,
+        }),
+        size: AtomicU64::new(size),
+        dropped: Default::default(),
+        senders_closed: Default::default(),
+        receiver_closed: Default::default(),
+        written_notify: Notify::new(),
+        acked_notify: Notify::new(),
+    });
+    let mut receiver = Receiver {
+        shared: shared.clone(),
+        reader: None,
+        position,
+        acknowledged: position,
+        delivered: None,
+        oldest,
+    };
+    // removes the segments acknowledged right before a crash
+    receiver.remove_acknowledged_segments(position).await;
+    Ok((
+        Sender {
+            guard: Arc::new(SenderGuard(shared)),
+        },
+        receiver,
+    ))
+}
+
+/// Notifies the receiver once the last sender is dropped.
+#[derive(Debug)]
+struct SenderGuard(Arc<Shared>);
+
+impl Drop for SenderGuard {
+    fn drop(&mut self) {
+        self.0.senders_closed.store(true, Ordering::SeqCst);
+        self.0.written_notify.notify_one();
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Sender {
+    guard: Arc<SenderGuard>,
+}
+
+impl Sender {
+    fn shared(&self) -> &Shared {
+        &self.guard.0
+    }
+
+    #[cfg(test)]
+    pub fn dropped(&self) -> u64 {
+        self.shared().dropped.load(Ordering::Relaxed)
+    }
+
+    fn count_dropped(&self) {
+        let total = self.shared().dropped.fetch_add(1, Ordering::Relaxed) + 1;
+        tracing::debug!("buffer full, event dropped ({total} in total)");
+    }
+
+    pub async fn send(&self, event: Event) -> Result<(), SendError<Event>> {
+        let shared = self.shared();
+        let encoded = match serde_json::to_vec(&event) {
+            Ok(inner) => inner,
+            Err(err) => {
+                tracing::error!("unable to encode event, discarding: {err:?}");
+                return Ok(());
+            }
+        };
+        let record_size = RECORD_HEADER_SIZE + encoded.len() as u64;
+        loop {
+            let acked = shared.acked_notify.notified();
+            tokio::pin!(acked);
+            acked.as_mut().enable();
+
+            let mut writer = shared.writer.lock().await;
+            if shared.receiver_closed.load(Ordering::SeqCst) {
+                return Err(SendError(event));
+            }
+            let size = shared.size.load(Ordering::SeqCst);
+            if size == 0 || size + record_size <= shared.max_size {
+                if let Err(err) = writer.append(shared, &encoded).await {
+                    tracing::error!("unable to write event in buffer, discarding: {err:?}");
+                }
+                return Ok(());
+            }
+            drop(writer);
+            match shared.when_full {
+                WhenFull::Block => acked.await,
+                // dropping the oldest events is rejected when validating the configuration
+                WhenFull::DropNewest | WhenFull::DropOldest => {
+                    self.count_dropped();
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Reads the state of the buffer without keeping it open.
+#[derive(Clone, Debug)]
+pub struct Probe {
+    shared: std::sync::Weak<Shared>,
+}
+
+impl Probe {
+    pub fn dropped(&self) -> Option<u64> {
+        let shared = self.shared.upgrade()?;
+        Some(shared.dropped.load(Ordering::Relaxed))
+    }
+
+    pub fn utilization(&self) -> Option<f64> {
+        let shared = self.shared.upgrade()?;
+        Some(shared.size.load(Ordering::SeqCst) as f64 / shared.max_size.max(1) as f64)
+    }
+}
+
+#[derive(Debug)]
+pub struct Receiver {
+    shared: Arc<Shared>,
+    reader: Option<(u64, BufReader<tokio::fs::File>)>,
+    /// Position right after the last received event
+    position: Position,
+    /// Position right after the last acknowledged event
+    acknowledged: Position,
+    /// Position and size of the events delivered to the component, acknowledged when it
+    /// asks for the next ones
+    delivered: Option<(Position, u64)>,
+    /// Oldest segment that may still be on disk
+    oldest: u64,
+}
+
+impl Drop for Receiver {
+    fn drop(&mut self) {
+        self.shared.receiver_closed.store(true, Ordering::SeqCst);
+        self.shared.acked_notify.notify_waiters();
+    }
+}
+
+impl Receiver {
+    pub fn probe(&self) -> Probe {
+        Probe {
+            shared: Arc::downgrade(&self.shared),
+        }
+    }
+
+    pub async fn recv(&mut self) -> Option<Event> {
+        self.acknowledge().await;
+        let (event, size) = self.next(true).await?;
+        self.delivered = Some((self.position, size));
+        Some(event)
+    }
+
+    pub async fn recv_many(&mut self, buffer: &mut Vec<Event>, limit: usize) -> usize {
+        self.acknowledge().await;
+        let mut count = 0;
+        let mut total = 0;
+        while count < limit {
+            let Some((event, size)) = self.next(count == 0).await else {
+                break;
+            };
+            buffer.push(event);
+            count += 1;
+            total += size;
+        }
+        if count > 0 {
+            self.delivered = Some((self.position, total));
+        }
+        count
+    }
+
+    async fn acknowledge(&mut self) {
+        let Some((position, size)) = self.delivered.take() else {
+            return;
+        };
+        if let Err(err) = write_checkpoint(&self.shared.directory, position).await {
+            tracing::error!("unable to write buffer checkpoint: {err:?}");
+        }
+        self.shared.size.fetch_sub(size, Ordering::SeqCst);
+        self.acknowledged = position;
+        self.remove_acknowledged_segments(position).await;
+        self.shared.acked_notify.notify_waiters();
+    }
+
+    /// Number of events that haven't been acknowledged yet, received or not.
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        use std::io::{Read, Seek};
+
+        let written = *self.shared.written.lock().unwrap();
+        let mut count = 0;
+        for segment in self.acknowledged.segment..=written.segment {
+            let Ok(mut file) = std::fs::File::open(segment_path(&self.shared.directory, segment))
+            else {
+                continue;
+            };
+            let mut offset = match segment == self.acknowledged.segment {
+                true => self.acknowledged.offset,
+                false => 0,
+            };
+            let mut header = [0u8; RECORD_HEADER_SIZE as usize];
+            while file.seek(SeekFrom::Start(offset)).is_ok() && file.read_exact(&mut header).is_ok()
+            {
+                offset += RECORD_HEADER_SIZE + u32::from_le_bytes(header) as u64;
+                count += 1;
+            }
+        }
+        count
+    }
+
+    #[cfg(test)]
+    pub fn is_closed(&self) -> bool {
+        self.shared.senders_closed.load(Ordering::SeqCst)
+    }
+
+    async fn remove_acknowledged_segments(&mut self, position: Position) {
+        while self.oldest < position.segment {
+            let path = segment_path(&self.shared.directory, self.oldest);
+            match tokio::fs::remove_file(&path).await {
+                Ok(_) => {}
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => tracing::warn!("unable to remove buffer segment {path:?}: {err:?}"),
+            }
+            self.oldest += 1;
+        }
+    }
+
+    /// Reads the next event and the size it takes on disk. When `wait` is false, only
+    /// returns the events already written.
+    async fn next(&mut self, wait: bool) -> Option<(Event, u64)> {
+        let mut skipped = 0;
+        loop {
+            // checking the senders before the position so that the last written events
+            // are not missed
+            let closed = self.shared.senders_closed.load(Ordering::SeqCst);
+            let written = *self.shared.written.lock().unwrap();
+            if self.position >= written {
+                if closed || !wait {
+                    self.shared.size.fetch_sub(skipped, Ordering::SeqCst);
+                    return None;
+                }
+                self.shared.written_notify.notified().await;
+                continue;
+            }
+            match self.read_record().await {
+                Ok(Some((event, size))) => return Some((event, size + skipped)),
+                Ok(None) => {}
+                Err(err) => tracing::error!("unable to read buffer segment: {err:?}"),
+            }
+            // the end of the segment has been reached or the rest of it can't be read
+            skipped += self.remaining_size().await;
+            self.reader = None;
+            self.position = Position {
+                segment: self.position.segment + 1,
+                offset: 0,
+            };
+        }
+    }
+
+    async fn remaining_size(&self) -> u64 {
+        let path = segment_path(&self.shared.directory, self.position.segment);
+        match tokio::fs::metadata(path).await {
+            Ok(metadata) => metadata.len().saturating_sub(self.position.offset),
+            Err(_) => 0,
+        }
+    }
+
+    async fn reader(&mut self) -> std::io::Result<Option<&mut BufReader<tokio::fs::File>>> {
+        let segment = self.position.segment;
+        if !matches!(self.reader, Some((current, _)) if current == segment) {
+            let path = segment_path(&self.shared.directory, segment);
+            let mut file = match tokio::fs::File::open(path).await {
+                Ok(inner) => inner,
+                Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(err),
+            };
+            file.seek(SeekFrom::Start(self.position.offset)).await?;
+            self.reader = Some((segment, BufReader::new(file)));
+        }
+        Ok(self.reader.as_mut().map(|(_, reader)| reader))
+    }
+
+    /// Reads the next record of the current segment, returns `None` at the end of it.
+    async fn read_record(&mut self) -> std::io::Result<Option<(Event, u64)>> {
+        loop {
+            let Some(reader) = self.reader().await? else {
+                return Ok(None);
+            };
+            let mut header = [0u8; RECORD_HEADER_SIZE as usize];
+            match reader.read_exact(&mut header).await {
+                Ok(_) => {}
+                Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+                Err(err) => return Err(err),
+            }
+            let mut encoded = vec![0u8; u32::from_le_bytes(header) as usize];
+            match reader.read_exact(&mut encoded).await {
+                Ok(_) => {}
+                Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+                Err(err) => return Err(err),
+            }
+            let size = RECORD_HEADER_SIZE + encoded.len() as u64;
+            self.position.offset += size;
+            match serde_json::from_slice(&encoded) {
+                Ok(event) => return Ok(Some((event, size))),
+                Err(err) => {
+                    tracing::warn!("unable to decode buffered event, skipping: {err:?}");
+                    self.shared.size.fetch_sub(size, Ordering::SeqCst);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::open;
+    use crate::components::buffer::WhenFull;
+    use crate::event::log::EventLog;
+
+    fn directory(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("tiny-vector-disk-buffer-{name}"));
+        let _ = std::fs::remove_dir_all(&path);
+        path
+    }
+
+    /// Maximum size only fitting a single record of the given message
+    fn single_record_size(message: &str) -> u64 {
+        let event: crate::event::Event = EventLog::new(message).into();
+        let size = super::RECORD_HEADER_SIZE + serde_json::to_vec(&event).unwrap().len() as u64;
+        size + size / 2
+    }
+
+    fn count_segments(directory: &PathBuf) -> usize {
+        std::fs::read_dir(directory)
+            .unwrap()
+            .filter(|entry| super::segment_id(&entry.as_ref().unwrap().path()).is_some())
+            .count()
+    }
+
+    #[tokio::test]
+    async fn should_receive_until_senders_are_dropped() {
+        let directory = directory("until-dropped");
+        let (tx, mut rx) = open(&directory, 1024 * 1024, WhenFull::Block)
+            .await
+            .unwrap();
+        let other = tx.clone();
+        tx.send(EventLog::new("first").into()).await.unwrap();
+        other.send(EventLog::new("second").into()).await.unwrap();
+        drop(tx);
+        drop(other);
+        let mut messages = Vec::new();
+        while let Some(event) = rx.recv().await {
+            messages.push(event.into_event_log().unwrap().message);
+        }
+        assert_eq!(messages, vec!["first", "second"]);
+    }
+
+    #[tokio::test]
+    async fn should_keep_unparseable_timestamps() {
+        let directory = directory("unparseable-timestamp");
+        let (tx, mut rx) = open(&directory, 1024 * 1024, WhenFull::Block)
+            .await
+            .unwrap();
+        let event: EventLog =
+            serde_json::from_str(r#"{"message": "hello", "timestamp": "17/Oct/2024"}"#).unwrap();
+        tx.send(event.clone().into()).await.unwrap();
+        drop(tx);
+        assert_eq!(rx.recv().await.unwrap().into_event_log(), Some(event));
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn should_replay_unacknowledged_events() {
+        let directory = directory("replay");
+        {
+            let (tx, mut rx) = open(&directory, 1024 * 1024, WhenFull::Block)
+                .await
+                .unwrap();
+            for message in ["first", "second", "third"] {
+                tx.send(EventLog::new(message).into()).await.unwrap();
+            }
+            // the first one is acknowledged when asking for the second one
+            rx.recv().await.unwrap();
+            rx.recv().await.unwrap();
+        }
+        let (tx, mut rx) = open(&directory, 1024 * 1024, WhenFull::Block)
+            .await
+            .unwrap();
+        tx.send(EventLog::new("fourth").into()).await.unwrap();
+        drop(tx);
+        let mut messages = Vec::new();
+        while let Some(event) = rx.recv().await {
+            messages.push(event.into_event_log().unwrap().message);
+        }
+        assert_eq!(messages, vec!["second", "third", "fourth"]);
+    }
+
+    #[tokio::test]
+    async fn should_remove_acknowledged_segments() {
+        let directory = directory("segments");
+        // segments are an eighth of the maximum size, small enough to have one event each
+        let max_size = single_record_size("second") * 4;
+        let (tx, mut rx) = open(&directory, max_size, WhenFull::Block).await.unwrap();
+        for message in ["first", "second", "third"] {
+            tx.send(EventLog::new(message).into()).await.unwrap();
+        }
+        assert_eq!(count_segments(&directory), 3);
+        drop(tx);
+        let mut buffer = Vec::new();
+        assert_eq!(rx.recv_many(&mut buffer, 10).await, 3);
+        assert_eq!(rx.recv_many(&mut buffer, 10).await, 0);
+        assert_eq!(count_segments(&directory), 1);
+    }
+
+    #[tokio::test]
+    async fn should_count_unacknowledged_events() {
+        let directory = directory("count");
+        let (tx, mut rx) = open(&directory, 1024 * 1024, WhenFull::Block)
+            .await
+            .unwrap();
+        for message in ["first", "second", "third"] {
+            tx.send(EventLog::new(message).into()).await.unwrap();
+        }
+        assert_eq!(rx.len(), 3);
+        rx.recv().await.unwrap();
+        assert_eq!(rx.len(), 3);
+        rx.recv().await.unwrap();
+        assert_eq!(rx.len(), 2);
+        assert!(!rx.is_closed());
+        drop(tx);
+        assert!(rx.is_closed());
+    }
+
+    #[tokio::test]
+    async fn should_drop_newest_when_full() {
+        let directory = directory("drop-newest");
+        let (tx, mut rx) = open(
+            &directory,
+            single_record_size("first"),
+            WhenFull::DropNewest,
+        )
+        .await
+        .unwrap();
+        for message in ["first", "second", "third"] {
+            tx.send(EventLog::new(message).into()).await.unwrap();
+        }
+        assert_eq!(tx.dropped(), 2);
+        drop(tx);
+        let first = rx.recv().await.unwrap().into_event_log().unwrap();
+        assert_eq!(first.message, "first");
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn should_block_until_acknowledged() {
+        let directory = directory("block");
+        let (tx, mut rx) = open(&directory, single_record_size("first"), WhenFull::Block)
+            .await
+            .unwrap();
+        tx.send(EventLog::new("first").into()).await.unwrap();
+        let blocked = tokio::time::timeout(
+            tokio::time::Duration::from_millis(50),
+            tx.send(EventLog::new("second").into()),
+        )
+        .await;
+        assert!(blocked.is_err());
+        rx.recv().await.unwrap();
+        let sending = tokio::spawn(async move { tx.send(EventLog::new("third").into()).await });
+        // asking for the next event acknowledges the first one and unblocks the sender
+        let third = rx.recv().await.unwrap().into_event_log().unwrap();
+        assert_eq!(third.message, "third");
+        sending.await.unwrap().unwrap();
+    }
+}
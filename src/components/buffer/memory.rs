@@ -0,0 +1,195 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+
+use tokio::sync::mpsc::error::{SendError, TrySendError};
+use tokio::sync::{mpsc, Mutex};
+
+use super::WhenFull;
+use crate::event::Event;
+
+pub fn channel(capacity: usize, when_full: WhenFull) -> (Sender, Receiver) {
+    let (sender, receiver) = mpsc::channel(capacity.max(1));
+    let receiver = Arc::new(Mutex::new(receiver));
+    let dropped = Arc::new(AtomicU64::default());
+    let probe = Probe {
+        sender: sender.downgrade(),
+        dropped: dropped.clone(),
+    };
+    (
+        Sender {
+            inner: sender,
+            when_full,
+            receiver: Arc::downgrade(&receiver),
+            dropped,
+        },
+        Receiver {
+            inner: receiver,
+            probe,
+        },
+    )
+}
+
+#[derive(Clone, Debug)]
+pub struct Sender {
+    inner: mpsc::Sender<Event>,
+    when_full: WhenFull,
+    /// Used to discard the oldest events when the buffer is full, the receiver is
+    /// owned by the receiving component so it doesn't prevent the channel from closing.
+    receiver: Weak<Mutex<mpsc::Receiver<Event>>>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Sender {
+    #[cfg(test)]
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn count_dropped(&self) {
+        let total = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+        tracing::debug!("buffer full, event dropped ({total} in total)");
+    }
+
+    pub async fn send(&self, event: Event) -> Result<(), SendError<Event>> {
+        match self.when_full {
+            WhenFull::Block => self.inner.send(event).await,
+            WhenFull::DropNewest => match self.inner.try_send(event) {
+                Ok(_) => Ok(()),
+                Err(TrySendError::Full(_)) => {
+                    self.count_dropped();
+                    Ok(())
+                }
+                Err(TrySendError::Closed(event)) => Err(SendError(event)),
+            },
+            WhenFull::DropOldest => {
+                let mut event = event;
+                loop {
+                    match self.inner.try_send(event) {
+                        Ok(_) => return Ok(()),
+                        Err(TrySendError::Full(inner)) => {
+                            event = inner;
+                        }
+                        Err(TrySendError::Closed(event)) => return Err(SendError(event)),
+                    }
+                    let Some(receiver) = self.receiver.upgrade() else {
+                        return Err(SendError(event));
+                    };
+                    if receiver.lock().await.try_recv().is_ok() {
+                        self.count_dropped();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Reads the state of the channel without keeping it open.
+#[derive(Clone, Debug)]
+pub struct Probe {
+    sender: mpsc::WeakSender<Event>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl Probe {
+    pub fn dropped(&self) -> Option<u64> {
+        Some(self.dropped.load(Ordering::Relaxed))
+    }
+
+    pub fn utilization(&self) -> Option<f64> {
+        let sender = self.sender.upgrade()?;
+        Some(1.0 - sender.capacity() as f64 / sender.max_capacity() as f64)
+    }
+}
+
+#[derive(Debug)]
+pub struct Receiver {
+    inner: Arc<Mutex<mpsc::Receiver<Event>>>,
+    probe: Probe,
+}
+
+impl Receiver {
+    pub fn probe(&self) -> Probe {
+        self.probe.clone()
+    }
+
+    pub async fn recv(&mut self) -> Option<Event> {
+        self.inner.lock().await.recv().await
+    }
+
+    pub async fn recv_many(&mut self, buffer: &mut Vec<Event>, limit: usize) -> usize {
+        self.inner.lock().await.recv_many(buffer, limit).await
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.inner.try_lock().map(|inner| inner.len()).unwrap_or(0)
+    }
+
+    #[cfg(test)]
+    pub fn is_closed(&self) -> bool {
+        self.inner
+            .try_lock()
+            .map(|inner| inner.is_closed())
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::channel;
+    use crate::components::buffer::WhenFull;
+    use crate::event::log::EventLog;
+
+    #[tokio::test]
+    async fn should_block_when_full() {
+        let (tx, mut rx) = channel(2, WhenFull::Block);
+        tx.send(EventLog::new("first").into()).await.unwrap();
+        tx.send(EventLog::new("second").into()).await.unwrap();
+        let blocked = tokio::time::timeout(
+            tokio::time::Duration::from_millis(50),
+            tx.send(EventLog::new("third").into()),
+        )
+        .await;
+        assert!(blocked.is_err());
+        assert_eq!(tx.dropped(), 0);
+        let first = rx.recv().await.unwrap().into_event_log().unwrap();
+        assert_eq!(first.message, "first");
+    }
+
+    #[tokio::test]
+    async fn should_drop_newest_when_full() {
+        let (tx, mut rx) = channel(2, WhenFull::DropNewest);
+        for message in ["first", "second", "third"] {
+            tx.send(EventLog::new(message).into()).await.unwrap();
+        }
+        assert_eq!(tx.dropped(), 1);
+        drop(tx);
+        let mut messages = Vec::new();
+        while let Some(event) = rx.recv().await {
+            messages.push(event.into_event_log().unwrap().message);
+        }
+        assert_eq!(messages, vec!["first", "second"]);
+    }
+
+    #[tokio::test]
+    async fn should_drop_oldest_when_full() {
+        let (tx, mut rx) = channel(2, WhenFull::DropOldest);
+        for message in ["first", "second", "third"] {
+            tx.send(EventLog::new(message).into()).await.unwrap();
+        }
+        assert_eq!(tx.dropped(), 1);
+        drop(tx);
+        let mut messages = Vec::new();
+        while let Some(event) = rx.recv().await {
+            messages.push(event.into_event_log().unwrap().message);
+        }
+        assert_eq!(messages, vec!["second", "third"]);
+    }
+
+    #[tokio::test]
+    async fn should_fail_when_receiver_is_dropped() {
+        let (tx, rx) = channel(2, WhenFull::DropOldest);
+        drop(rx);
+        assert!(tx.send(EventLog::new("first").into()).await.is_err());
+    }
+}
@@ -0,0 +1,322 @@
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::mpsc::error::SendError;
+
+use super::metrics::ComponentMetrics;
+use crate::event::Event;
+
+pub mod disk;
+pub mod memory;
+
+const DEFAULT_CAPACITY: usize = 1000;
+const DEFAULT_MAX_SIZE: u64 = 256 * 1024 * 1024;
+
+fn default_capacity() -> usize {
+    DEFAULT_CAPACITY
+}
+
+fn default_max_size() -> u64 {
+    DEFAULT_MAX_SIZE
+}
+
+/// Where the events waiting to be handled by the component are kept.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BufferType {
+    #[default]
+    Memory,
+    /// Persists the events in segment files so that they survive crashes and restarts,
+    /// only available for sinks. The segments are only synced to the disk once full, the
+    /// last events written can be lost on a power loss.
+    Disk,
+}
+
+/// What to do with an event when the buffer of the receiving component is full.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WhenFull {
+    /// Wait for the receiving component to make some room, slowing down the upstream components
+    #[default]
+    Block,
+    /// Discard the event being sent
+    DropNewest,
+    /// Discard the oldest event of the buffer to make some room for the event being sent,
+    /// only available for memory buffers
+    DropOldest,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize)]
+pub struct BufferConfig {
+    #[serde(default, rename = "type")]
+    pub kind: BufferType,
+    /// Maximum number of events waiting to be handled by the component, for memory buffers
+    #[serde(default = "default_capacity")]
+    pub capacity: usize,
+    /// Maximum size, in bytes, of the events waiting on disk, for disk buffers
+    #[serde(default = "default_max_size")]
+    pub max_size: u64,
+    #[serde(default)]
+    pub when_full: WhenFull,
+}
+
+impl Default for BufferConfig {
+    fn default() -> Self {
+        Self {
+            kind: BufferType::default(),
+            capacity: DEFAULT_CAPACITY,
+            max_size: DEFAULT_MAX_SIZE,
+            when_full: WhenFull::default(),
+        }
+    }
+}
+
+impl BufferConfig {
+    /// Creates the buffer, disk buffers are stored in the given directory.
+    pub async fn build(&self, directory: &Path) -> std::io::Result<(Sender, Receiver)> {
+        match self.kind {
+            BufferType::Memory => {
+                let (sender, receiver) = memory::channel(self.capacity, self.when_full);
+                Ok((Sender::Memory(sender), receiver.into()))
+            }
+            BufferType::Disk => {
+                let (sender, receiver) =
+                    disk::open(directory, self.max_size, self.when_full).await?;
+                Ok((Sender::Disk(sender), receiver.into()))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Sender {
+    Memory(memory::Sender),
+    Disk(disk::Sender),
+}
+
+impl Sender {
+    pub async fn send(&self, event: Event) -> Result<(), SendError<Event>> {
+        match self {
+            Self::Memory(inner) => inner.send(event).await,
+            Self::Disk(inner) => inner.send(event).await,
+        }
+    }
+}
+
+/// Reads the state of a buffer without keeping it open.
+#[derive(Clone, Debug)]
+pub enum Probe {
+    Memory(memory::Probe),
+    Disk(disk::Probe),
+}
+
+impl Probe {
+    /// Number of events that have been discarded because the buffer was full
+    pub fn dropped(&self) -> Option<u64> {
+        match self {
+            Self::Memory(inner) => inner.dropped(),
+            Self::Disk(inner) => inner.dropped(),
+        }
+    }
+
+    /// Ratio between the events waiting in the buffer and its capacity
+    pub fn utilization(&self) -> Option<f64> {
+        match self {
+            Self::Memory(inner) => inner.utilization(),
+            Self::Disk(inner) => inner.utilization(),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum ReceiverInner {
+    Memory(memory::Receiver),
+    Disk(Box<disk::Receiver>),
+}
+
+/// Keeps the receiving side of a buffer between two runs of a component.
+///
+/// A receiver attached to a slot goes back into it when dropped, even when the component
+/// panics, so that the channel stays open and the component can be restarted on it.
+#[derive(Clone, Debug, Default)]
+pub struct ReceiverSlot(Arc<Mutex<Option<ReceiverInner>>>);
+
+impl ReceiverSlot {
+    /// Takes the receiver back, if it's not used by a running component.
+    pub fn take(&self) -> Option<Receiver> {
+        let inner = self.0.lock().unwrap().take()?;
+        Some(Receiver {
+            inner: Some(inner),
+            metrics: Default::default(),
+            slot: Some(self.clone()),
+        })
+    }
+
+    /// Drops the receiver, if it's not used by a running component, closing the channel.
+    pub fn close(&self) {
+        drop(self.0.lock().unwrap().take());
+    }
+}
+
+#[derive(Debug)]
+pub struct Receiver {
+    /// Only empty while being dropped
+    inner: Option<ReceiverInner>,
+    metrics: Arc<ComponentMetrics>,
+    slot: Option<ReceiverSlot>,
+}
+
+impl Drop for Receiver {
+    fn drop(&mut self) {
+        if let (Some(slot), Some(inner)) = (self.slot.take(), self.inner.take()) {
+            if let Ok(mut guard) = slot.0.lock() {
+                *guard = Some(inner);
+            }
+        }
+    }
+}
+
+impl From<ReceiverInner> for Receiver {
+    fn from(value: ReceiverInner) -> Self {
+        Self {
+            inner: Some(value),
+            metrics: Default::default(),
+            slot: None,
+        }
+    }
+}
+
+impl From<memory::Receiver> for Receiver {
+    fn from(value: memory::Receiver) -> Self {
+        ReceiverInner::Memory(value).into()
+    }
+}
+
+impl From<disk::Receiver> for Receiver {
+    fn from(value: disk::Receiver) -> Self {
+        ReceiverInner::Disk(Box::new(value)).into()
+    }
+}
+
+impl Receiver {
+    /// Counts the received events in the metrics of the component.
+    pub fn with_metrics(mut self, metrics: Arc<ComponentMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Gives the receiver back to the slot once dropped.
+    pub fn attach(mut self, slot: &ReceiverSlot) -> Self {
+        self.slot = Some(slot.clone());
+        self
+    }
+
+    fn inner(&self) -> &ReceiverInner {
+        self.inner.as_ref().expect("receiver already dropped")
+    }
+
+    fn inner_mut(&mut self) -> &mut ReceiverInner {
+        self.inner.as_mut().expect("receiver already dropped")
+    }
+
+    pub fn probe(&self) -> Probe {
+        match self.inner() {
+            ReceiverInner::Memory(inner) => Probe::Memory(inner.probe()),
+            ReceiverInner::Disk(inner) => Probe::Disk(inner.probe()),
+        }
+    }
+
+    pub async fn recv(&mut self) -> Option<Event> {
+        let event = match self.inner_mut() {
+            ReceiverInner::Memory(inner) => inner.recv().await,
+            ReceiverInner::Disk(inner) => inner.recv().await,
+        }?;
+        self.metrics.events_in.fetch_add(1, Ordering::Relaxed);
+        Some(event)
+    }
+
+    pub async fn recv_many(&mut self, buffer: &mut Vec<Event>, limit: usize) -> usize {
+        let count = match self.inner_mut() {
+            ReceiverInner::Memory(inner) => inner.recv_many(buffer, limit).await,
+            ReceiverInner::Disk(inner) => inner.recv_many(buffer, limit).await,
+        };
+        self.metrics
+            .events_in
+            .fetch_add(count as u64, Ordering::Relaxed);
+        count
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        match self.inner() {
+            ReceiverInner::Memory(inner) => inner.len(),
+            ReceiverInner::Disk(inner) => inner.len(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[cfg(test)]
+    pub fn is_closed(&self) -> bool {
+        match self.inner() {
+            ReceiverInner::Memory(inner) => inner.is_closed(),
+            ReceiverInner::Disk(inner) => inner.is_closed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BufferConfig, BufferType, ReceiverSlot, WhenFull};
+    use crate::event::log::EventLog;
+
+    #[tokio::test]
+    async fn should_keep_channel_open_when_attached_receiver_is_dropped() {
+        let (sender, receiver) = super::memory::channel(10, WhenFull::Block);
+        let slot = ReceiverSlot::default();
+        let receiver = super::Receiver::from(receiver).attach(&slot);
+        let task = tokio::spawn(async move {
+            let _receiver = receiver;
+            panic!("component crashed");
+        });
+        assert!(task.await.unwrap_err().is_panic());
+        sender.send(EventLog::new("hello").into()).await.unwrap();
+        let mut receiver = slot.take().unwrap();
+        assert!(slot.take().is_none());
+        let event = receiver.recv().await.unwrap().into_event_log().unwrap();
+        assert_eq!(event.message, "hello");
+    }
+
+    #[tokio::test]
+    async fn should_close_channel_with_slot() {
+        let (sender, receiver) = super::memory::channel(10, WhenFull::Block);
+        let slot = ReceiverSlot::default();
+        drop(super::Receiver::from(receiver).attach(&slot));
+        slot.close();
+        assert!(slot.take().is_none());
+        assert!(sender.send(EventLog::new("hello").into()).await.is_err());
+    }
+
+    #[test]
+    fn should_deserialize_with_defaults() {
+        let config: BufferConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(config, BufferConfig::default());
+        let config: BufferConfig = serde_json::from_str(
+            r#"{"type": "disk", "max_size": 1024, "when_full": "drop_newest"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            BufferConfig {
+                kind: BufferType::Disk,
+                max_size: 1024,
+                when_full: WhenFull::DropNewest,
+                ..Default::default()
+            }
+        );
+    }
+}
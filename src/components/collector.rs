@@ -1,31 +1,195 @@
 use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
 
+use arc_swap::ArcSwap;
+use tokio::sync::broadcast;
 use tokio::sync::mpsc::error::SendError;
 
+use super::metrics::ComponentMetrics;
+use super::name::ComponentName;
 use super::output::NamedOutput;
 use crate::event::{CowStr, Event};
 use crate::prelude::Sender;
 
+/// Senders of the components consuming each output, an output can feed several components.
 #[derive(Clone, Debug, Default)]
-pub struct Collector {
-    default: Option<Sender>,
-    others: HashMap<CowStr, Sender>,
+struct Outputs {
+    default: Vec<Sender>,
+    others: HashMap<CowStr, Vec<Sender>>,
 }
 
-impl Collector {
-    pub fn add_output(&mut self, named: NamedOutput, sender: Sender) {
+impl Outputs {
+    fn get(&self, named: &NamedOutput) -> &[Sender] {
         match named {
-            NamedOutput::Default => {
-                self.default = Some(sender);
+            NamedOutput::Default => &self.default,
+            NamedOutput::Named(inner) => self
+                .others
+                .get(inner.as_ref())
+                .map(Vec::as_slice)
+                .unwrap_or_default(),
+        }
+    }
+
+    fn senders(&self) -> impl Iterator<Item = &Sender> {
+        self.default.iter().chain(self.others.values().flatten())
+    }
+}
+
+/// Sends a copy of the event to every sender, only failing when none of them accepted it.
+///
+/// The senders are awaited one after the other, so a consumer with a full blocking buffer
+/// slows down the delivery to all the consumers of the output. The consumers that can't
+/// receive the event while others did are counted as errors of the sending component.
+async fn broadcast<'a>(
+    senders: impl Iterator<Item = &'a Sender>,
+    event: Event,
+    metrics: &ComponentMetrics,
+) -> Result<(), SendError<Event>> {
+    let mut delivered = false;
+    let mut failures = Vec::new();
+    for sender in senders {
+        match sender.send(event.clone()).await {
+            Ok(_) => delivered = true,
+            Err(err) => {
+                tracing::warn!("unable to send event to a consumer, its buffer is closed");
+                failures.push(err);
             }
-            NamedOutput::Named(inner) => {
-                self.others.insert(inner, sender);
+        }
+    }
+    if !delivered {
+        return failures.pop().map_or(Ok(()), Err);
+    }
+    metrics
+        .errors
+        .fetch_add(failures.len() as u64, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Number of events kept for a tap that doesn't read fast enough, older ones get discarded.
+const TAP_CAPACITY: usize = 256;
+
+/// Copies of the events leaving each output, used to inspect a running component.
+///
+/// Sending to a tap never waits, so that tapping doesn't slow down the normal delivery.
+#[derive(Debug, Default)]
+pub struct Taps {
+    outputs: ArcSwap<HashMap<NamedOutput, broadcast::Sender<Event>>>,
+    /// Prevents two new taps from overriding each other
+    registering: Mutex<()>,
+}
+
+impl Taps {
+    /// Receives a copy of the events sent to the given output.
+    pub fn subscribe(&self, output: &NamedOutput) -> broadcast::Receiver<Event> {
+        let _guard = self.registering.lock().unwrap();
+        let current = self.outputs.load();
+        if let Some(sender) = current.get(output) {
+            return sender.subscribe();
+        }
+        let (sender, receiver) = broadcast::channel(TAP_CAPACITY);
+        let mut next = HashMap::clone(&current);
+        next.insert(output.clone(), sender);
+        self.outputs.store(Arc::new(next));
+        receiver
+    }
+
+    fn send(&self, output: Option<&NamedOutput>, event: &Event) {
+        let outputs = self.outputs.load();
+        let senders = outputs
+            .iter()
+            .filter(|(name, _)| output.is_none_or(|output| output == *name))
+            .filter(|(_, sender)| sender.receiver_count() > 0);
+        for (_, sender) in senders {
+            let _ = sender.send(event.clone());
+        }
+    }
+}
+
+/// What the collector records in the metadata of the events it sends.
+#[derive(Clone, Debug, Default)]
+enum Stamp {
+    #[default]
+    Nothing,
+    /// Sets the source and the reception time, when not already set by a previous tiny-vector
+    Source(ComponentName),
+    /// Appends the transform to the hops
+    Transform(ComponentName),
+}
+
+impl Stamp {
+    fn apply(&self, event: &mut Event) {
+        match self {
+            Self::Nothing => {}
+            Self::Source(name) => {
+                let metadata = event.metadata_mut();
+                metadata
+                    .received_at
+                    .get_or_insert_with(crate::event::timestamp::now);
+                metadata.source.get_or_insert_with(|| name.clone());
             }
+            Self::Transform(name) => event.metadata_mut().hops.push(name.clone()),
         }
     }
+}
+
+/// Sends the events of a component to the components consuming its outputs.
+///
+/// The clones of a collector share the same outputs, so that the running topology can
+/// rewire a component by replacing all its outputs at once.
+#[derive(Clone, Debug, Default)]
+pub struct Collector {
+    outputs: Arc<ArcSwap<Outputs>>,
+    metrics: Arc<ComponentMetrics>,
+    taps: Arc<Taps>,
+    stamp: Stamp,
+}
+
+impl Collector {
+    /// Counts the sent events in the metrics of the component.
+    pub fn with_metrics(mut self, metrics: Arc<ComponentMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Records the source in the metadata of the sent events.
+    pub fn with_source(mut self, name: &ComponentName) -> Self {
+        self.stamp = Stamp::Source(name.clone());
+        self
+    }
+
+    /// Records the transform in the hops of the sent events.
+    pub fn with_transform(mut self, name: &ComponentName) -> Self {
+        self.stamp = Stamp::Transform(name.clone());
+        self
+    }
 
-    pub fn senders(&self) -> impl Iterator<Item = &Sender> {
-        self.default.iter().chain(self.others.values())
+    pub fn add_output(&mut self, named: NamedOutput, sender: Sender) {
+        self.outputs.rcu(|current| {
+            let mut next = Outputs::clone(current);
+            match named {
+                NamedOutput::Default => {
+                    next.default.push(sender.clone());
+                }
+                NamedOutput::Named(ref inner) => {
+                    next.others
+                        .entry(inner.clone())
+                        .or_default()
+                        .push(sender.clone());
+                }
+            }
+            next
+        });
+    }
+
+    /// Taps of the outputs, shared without keeping the outputs open.
+    pub fn taps(&self) -> Arc<Taps> {
+        self.taps.clone()
+    }
+
+    /// Atomically replaces the outputs with the ones from the other collector.
+    pub fn replace_with(&self, other: &Collector) {
+        self.outputs.store(other.outputs.load_full());
     }
 
     #[cfg(test)]
@@ -35,34 +199,159 @@ impl Collector {
     }
 
     pub async fn send_default(&self, event: Event) -> Result<(), SendError<Event>> {
-        match self.default {
-            Some(ref inner) => inner.send(event).await,
-            None => {
-                tracing::trace!("no default output, discarding event");
-                Ok(())
-            }
-        }
+        self.send_named(&NamedOutput::Default, event).await
     }
 
     pub async fn send_named(
         &self,
         output: &NamedOutput,
-        event: Event,
+        mut event: Event,
     ) -> Result<(), SendError<Event>> {
-        match output {
-            NamedOutput::Default => self.send_default(event).await?,
-            NamedOutput::Named(inner) => match self.others.get(inner.as_ref()) {
-                Some(inner) => inner.send(event).await?,
-                None => tracing::trace!("no {inner:?} output, discarding event"),
-            },
-        };
+        self.stamp.apply(&mut event);
+        self.taps.send(Some(output), &event);
+        let outputs = self.outputs.load_full();
+        let senders = outputs.get(output);
+        if senders.is_empty() {
+            match output.is_default() {
+                true => tracing::trace!("no default output, discarding event"),
+                false => tracing::trace!("no {output:?} output, discarding event"),
+            }
+            return Ok(());
+        }
+        broadcast(senders.iter(), event, &self.metrics).await?;
+        self.metrics.events_out.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
 
-    pub async fn send_all(&self, event: Event) -> Result<(), SendError<Event>> {
-        for sender in self.senders() {
-            sender.send(event.clone()).await?;
+    pub async fn send_all(&self, mut event: Event) -> Result<(), SendError<Event>> {
+        self.stamp.apply(&mut event);
+        self.taps.send(None, &event);
+        let outputs = self.outputs.load_full();
+        if outputs.senders().next().is_none() {
+            return Ok(());
         }
+        broadcast(outputs.senders(), event, &self.metrics).await?;
+        self.metrics.events_out.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use super::Collector;
+    use crate::components::metrics::ComponentMetrics;
+    use crate::components::name::ComponentName;
+    use crate::components::output::NamedOutput;
+    use crate::event::log::EventLog;
+    use crate::prelude::create_channel;
+
+    #[tokio::test]
+    async fn should_send_to_replaced_outputs() {
+        let (first_tx, first_rx) = create_channel(10);
+        let (second_tx, second_rx) = create_channel(10);
+        let collector = Collector::default().with_output(NamedOutput::Default, first_tx);
+        let running = collector.clone();
+
+        running
+            .send_default(EventLog::new("first").into())
+            .await
+            .unwrap();
+        collector.replace_with(&Collector::default().with_output(NamedOutput::Default, second_tx));
+        running
+            .send_default(EventLog::new("second").into())
+            .await
+            .unwrap();
+
+        assert_eq!(first_rx.len(), 1);
+        assert_eq!(second_rx.len(), 1);
+        assert!(first_rx.is_closed());
+    }
+
+    #[tokio::test]
+    async fn should_send_to_every_consumer_of_an_output() {
+        let (first_tx, first_rx) = create_channel(10);
+        let (second_tx, second_rx) = create_channel(10);
+        let (closed_tx, closed_rx) = create_channel(10);
+        drop(closed_rx);
+        let metrics = Arc::<ComponentMetrics>::default();
+        let collector = Collector::default()
+            .with_metrics(metrics.clone())
+            .with_output(NamedOutput::Default, first_tx)
+            .with_output(NamedOutput::Default, closed_tx)
+            .with_output(NamedOutput::Default, second_tx);
+
+        collector
+            .send_default(EventLog::new("hello").into())
+            .await
+            .unwrap();
+
+        assert_eq!(first_rx.len(), 1);
+        assert_eq!(second_rx.len(), 1);
+        // the closed consumer is counted as an error
+        assert_eq!(metrics.errors.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn should_copy_events_to_taps() {
+        let (tx, rx) = create_channel(10);
+        let collector = Collector::default().with_output(NamedOutput::Default, tx);
+        let mut tap = collector.taps().subscribe(&NamedOutput::Default);
+        let mut other = collector.taps().subscribe(&NamedOutput::named("other"));
+
+        collector
+            .send_default(EventLog::new("hello").into())
+            .await
+            .unwrap();
+
+        assert_eq!(rx.len(), 1);
+        let event = tap.try_recv().unwrap().into_event_log().unwrap();
+        assert_eq!(event.message, "hello");
+        assert!(other.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn should_fail_when_no_consumer_accepts_the_event() {
+        let (first_tx, first_rx) = create_channel(10);
+        let (second_tx, second_rx) = create_channel(10);
+        drop((first_rx, second_rx));
+        let collector = Collector::default()
+            .with_output(NamedOutput::named("first"), first_tx)
+            .with_output(NamedOutput::named("first"), second_tx);
+
+        let error = collector
+            .send_named(&NamedOutput::named("first"), EventLog::new("hello").into())
+            .await
+            .unwrap_err();
+        assert_eq!(error.0.into_event_log().unwrap().message, "hello");
+    }
+
+    #[tokio::test]
+    async fn should_stamp_metadata() {
+        let (tx, mut rx) = create_channel(10);
+        let source = Collector::default()
+            .with_output(NamedOutput::Default, tx.clone())
+            .with_source(&ComponentName::new("generator"));
+        let transform = Collector::default()
+            .with_output(NamedOutput::Default, tx)
+            .with_transform(&ComponentName::new("router"));
+
+        source
+            .send_default(EventLog::new("hello").with_source("upstream").into())
+            .await
+            .unwrap();
+        let event = rx.recv().await.unwrap();
+        // already stamped by a previous tiny-vector
+        assert_eq!(
+            event.metadata().source,
+            Some(ComponentName::new("upstream"))
+        );
+        assert!(event.metadata().received_at.is_some());
+
+        transform.send_default(event).await.unwrap();
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.metadata().hops, vec![ComponentName::new("router")]);
+    }
+}
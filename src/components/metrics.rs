@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+use once_cell::sync::Lazy;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+use super::buffer::Probe;
+use super::name::ComponentName;
+
+/// Name of the span wrapping the execution of each component.
+pub(crate) const COMPONENT_SPAN: &str = "component";
+
+static REGISTRY: Lazy<Registry> = Lazy::new(Registry::default);
+
+/// Counters updated by a running component.
+#[derive(Debug, Default)]
+pub struct ComponentMetrics {
+    pub events_in: AtomicU64,
+    pub events_out: AtomicU64,
+    pub errors: AtomicU64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    kind: &'static str,
+    flavor: &'static str,
+    metrics: Weak<ComponentMetrics>,
+    buffer: Option<Probe>,
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    components: Mutex<HashMap<ComponentName, Entry>>,
+}
+
+/// Registers a starting component, its metrics are kept as long as the component holds them.
+pub fn register(
+    name: &ComponentName,
+    kind: &'static str,
+    flavor: &'static str,
+    buffer: Option<Probe>,
+) -> Arc<ComponentMetrics> {
+    let metrics = Arc::new(ComponentMetrics::default());
+    let mut components = REGISTRY.components.lock().unwrap();
+    components.retain(|_, entry| entry.metrics.strong_count() > 0);
+    components.insert(
+        name.clone(),
+        Entry {
+            kind,
+            flavor,
+            metrics: Arc::downgrade(&metrics),
+            buffer,
+        },
+    );
+    metrics
+}
+
+pub(crate) fn find(name: &str) -> Option<Arc<ComponentMetrics>> {
+    let components = REGISTRY.components.lock().unwrap();
+    components.get(name)?.metrics.upgrade()
+}
+
+/// State of the metrics of a running component.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComponentSnapshot {
+    pub name: ComponentName,
+    pub kind: &'static str,
+    pub flavor: &'static str,
+    /// Only available for the components with inputs
+    pub events_in: Option<u64>,
+    /// Only available for the components with outputs
+    pub events_out: Option<u64>,
+    pub errors: u64,
+    pub dropped: Option<u64>,
+    pub channel_utilization: Option<f64>,
+}
+
+/// Lists the metrics of all the running components.
+pub fn snapshot() -> Vec<ComponentSnapshot> {
+    let components = REGISTRY.components.lock().unwrap();
+    components
+        .iter()
+        .filter_map(|(name, entry)| {
+            let metrics = entry.metrics.upgrade()?;
+            let buffer = entry.buffer.as_ref();
+            Some(ComponentSnapshot {
+                name: name.clone(),
+                kind: entry.kind,
+                flavor: entry.flavor,
+                events_in: buffer.map(|_| metrics.events_in.load(Ordering::Relaxed)),
+                events_out: (entry.kind != crate::sinks::COMPONENT_KIND)
+                    .then(|| metrics.events_out.load(Ordering::Relaxed)),
+                errors: metrics.errors.load(Ordering::Relaxed),
+                dropped: buffer.and_then(Probe::dropped),
+                channel_utilization: buffer.and_then(Probe::utilization),
+            })
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct NameVisitor(Option<String>);
+
+impl Visit for NameVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "name" {
+            self.0 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+/// Counts the errors logged by the components, within their span.
+pub struct ErrorCounterLayer;
+
+impl<S> tracing_subscriber::Layer<S> for ErrorCounterLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().name() != COMPONENT_SPAN {
+            return;
+        }
+        let mut visitor = NameVisitor::default();
+        attrs.record(&mut visitor);
+        let Some(metrics) = visitor.0.as_deref().and_then(find) else {
+            return;
+        };
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(metrics);
+        }
+    }
+
+    fn on_event(&self, event: &tracing::Event<'_>, ctx: Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR {
+            return;
+        }
+        let Some(scope) = ctx.event_scope(event) else {
+            return;
+        };
+        for span in scope {
+            if let Some(metrics) = span.extensions().get::<Arc<ComponentMetrics>>() {
+                metrics.errors.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::{register, snapshot, ErrorCounterLayer, COMPONENT_SPAN};
+    use crate::components::name::ComponentName;
+
+    #[test]
+    fn should_count_errors_within_component_span() {
+        let name = ComponentName::new("should-count-errors");
+        let metrics = register(&name, "transform", "route", None);
+        let subscriber = tracing_subscriber::registry().with(ErrorCounterLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::error!("outside of any component");
+            let span = tracing::info_span!(COMPONENT_SPAN, name = name.as_ref());
+            let _entered = span.enter();
+            tracing::warn!("not an error");
+            tracing::error!("something went wrong");
+            tracing::info_span!("nested").in_scope(|| {
+                tracing::error!("something else went wrong");
+            });
+        });
+        assert_eq!(metrics.errors.load(std::sync::atomic::Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn should_forget_stopped_components() {
+        let name = ComponentName::new("should-forget-stopped");
+        let metrics = register(&name, "source", "random_logs", None);
+        assert!(snapshot().iter().any(|item| item.name == name));
+        drop(metrics);
+        assert!(!snapshot().iter().any(|item| item.name == name));
+    }
+}
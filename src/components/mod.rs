@@ -1,7 +1,12 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
+use tokio::sync::mpsc::error::SendError;
 
+use crate::event::Event;
+
+pub(crate) mod buffer;
 pub(crate) mod collector;
+pub(crate) mod metrics;
 pub(crate) mod name;
 pub(crate) mod output;
 
@@ -12,6 +17,13 @@ fn validate_name(input: &str) -> bool {
     NAME_REGEX.is_match(input)
 }
 
+/// Why a component stopped on its own, considered as a failure by the restart policy.
+#[derive(Debug, thiserror::Error)]
+pub enum ExecutionError {
+    #[error("unable to send event downstream")]
+    UnableToSend(#[from] SendError<Event>),
+}
+
 #[cfg(test)]
 mod tests {
     #[test_case::test_case("foo"; "basic")]
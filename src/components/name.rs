@@ -42,6 +42,12 @@ impl AsRef<str> for ComponentName {
     }
 }
 
+impl std::borrow::Borrow<str> for ComponentName {
+    fn borrow(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
 impl std::fmt::Display for ComponentName {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.0.fmt(f)
@@ -49,11 +55,21 @@ impl std::fmt::Display for ComponentName {
 }
 
 impl ComponentName {
+    #[allow(dead_code)]
     pub fn into_string(self) -> String {
         self.0
     }
 }
 
+impl serde::Serialize for ComponentName {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
 impl<'de> serde::de::Deserialize<'de> for ComponentName {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
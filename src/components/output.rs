@@ -14,8 +14,9 @@ pub trait ComponentWithOutputs {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub enum NamedOutput {
+    #[default]
     Default,
     Named(CowStr),
 }
@@ -26,6 +27,7 @@ impl NamedOutput {
     }
 }
 
+#[cfg(test)]
 impl NamedOutput {
     pub fn named<N: Into<CowStr>>(name: N) -> Self {
         Self::Named(name.into())
@@ -89,12 +91,6 @@ impl<'de> serde::de::Deserialize<'de> for NamedOutput {
     }
 }
 
-impl Default for NamedOutput {
-    fn default() -> Self {
-        Self::Default
-    }
-}
-
 impl AsRef<str> for NamedOutput {
     fn as_ref(&self) -> &str {
         match self {
@@ -1,8 +1,10 @@
 use indexmap::IndexMap;
 
+use super::metadata::EventMetadata;
+use super::timestamp::Timestamp;
 use super::CowStr;
 
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize, derive_more::From)]
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize, derive_more::From)]
 #[serde(untagged)]
 pub enum EventLogAttribute {
     Text(CowStr),
@@ -10,9 +12,12 @@ pub enum EventLogAttribute {
     Integer(i64),
     Float(f64),
     Boolean(bool),
+    Array(Vec<EventLogAttribute>),
+    Map(IndexMap<CowStr, EventLogAttribute>),
+    /// Kept so that the json objects with a `null` value can be received
+    Null,
 }
 
-#[cfg(test)]
 impl EventLogAttribute {
     pub fn as_text(&self) -> Option<&str> {
         match self {
@@ -21,6 +26,20 @@ impl EventLogAttribute {
         }
     }
 
+    /// Reads an RFC3339 text or a time since epoch, like [`super::timestamp::optional`].
+    fn as_timestamp(&self) -> Option<Timestamp> {
+        match self {
+            Self::Text(inner) => super::timestamp::parse(inner, None),
+            Self::UInteger(inner) => super::timestamp::from_epoch(i64::try_from(*inner).ok()?),
+            Self::Integer(inner) => super::timestamp::from_epoch(*inner),
+            Self::Float(inner) => super::timestamp::from_epoch_f64(*inner),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+impl EventLogAttribute {
     pub fn as_uint(&self) -> Option<u64> {
         match self {
             Self::UInteger(inner) => Some(*inner),
@@ -62,11 +81,63 @@ impl From<String> for EventLogAttribute {
     }
 }
 
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+/// Key of the timestamp in the serialized events, so that it doesn't collide with a
+/// `timestamp` attribute that couldn't be parsed.
+pub const TIMESTAMP_FIELD: &str = "@timestamp";
+
+/// Key of the timestamp in the events serialized by the previous versions.
+const LEGACY_TIMESTAMP_FIELD: &str = "timestamp";
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(from = "ReceivedEventLog")]
 pub struct EventLog {
     #[serde(flatten)]
     pub attributes: IndexMap<CowStr, EventLogAttribute>,
     pub message: String,
+    /// When the event happened, set by the source or parsed from an attribute
+    #[serde(
+        rename = "@timestamp",
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "super::timestamp::optional::serialize"
+    )]
+    pub timestamp: Option<Timestamp>,
+    /// Carried next to the content of the event, see [`super::Event`]
+    #[serde(skip)]
+    pub metadata: EventMetadata,
+}
+
+/// Content of a log before its timestamp gets parsed, a value that can't be parsed being
+/// kept as an attribute instead of losing the event.
+#[derive(serde::Deserialize)]
+struct ReceivedEventLog {
+    #[serde(flatten)]
+    attributes: IndexMap<CowStr, EventLogAttribute>,
+    message: String,
+}
+
+impl From<ReceivedEventLog> for EventLog {
+    fn from(value: ReceivedEventLog) -> Self {
+        let ReceivedEventLog {
+            mut attributes,
+            message,
+        } = value;
+        let timestamp = [TIMESTAMP_FIELD, LEGACY_TIMESTAMP_FIELD]
+            .into_iter()
+            .find_map(|name| {
+                let attribute = attributes.get(name)?;
+                let timestamp = attribute.as_timestamp();
+                if timestamp.is_some() || matches!(attribute, EventLogAttribute::Null) {
+                    attributes.shift_remove(name);
+                }
+                timestamp
+            });
+        Self {
+            attributes,
+            message,
+            timestamp,
+            metadata: EventMetadata::default(),
+        }
+    }
 }
 
 impl EventLog {
@@ -74,6 +145,8 @@ impl EventLog {
         Self {
             attributes: IndexMap::new(),
             message: message.into(),
+            timestamp: None,
+            metadata: EventMetadata::default(),
         }
     }
 
@@ -86,11 +159,70 @@ impl EventLog {
         self
     }
 
-    pub fn add_attribute<K: Into<CowStr>, V: Into<EventLogAttribute>>(
-        &mut self,
-        name: K,
-        value: V,
-    ) {
-        self.attributes.insert(name.into(), value.into());
+    /// When the event happened, or when it has been received when unknown.
+    pub fn timestamp_or_received(&self) -> Option<Timestamp> {
+        self.timestamp.or(self.metadata.received_at)
+    }
+
+    pub fn with_timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+}
+
+#[cfg(test)]
+impl EventLog {
+    pub fn with_source(mut self, name: &str) -> Self {
+        self.metadata.source = Some(crate::components::name::ComponentName::new(name));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventLog, TIMESTAMP_FIELD};
+
+    #[test]
+    fn should_deserialize_attribute_types() {
+        let event: EventLog = serde_json::from_str(
+            r#"{"message": "hello", "count": 42, "offset": -3, "ratio": 0.5, "ok": true}"#,
+        )
+        .unwrap();
+        assert_eq!(event.attributes["count"].as_uint(), Some(42));
+        assert_eq!(event.attributes["offset"].as_int(), Some(-3));
+        assert_eq!(event.attributes["ratio"].as_float(), Some(0.5));
+        assert_eq!(event.attributes["ok"].as_bool(), Some(true));
+        assert_eq!(event.attributes["count"].as_int(), None);
+    }
+
+    #[test]
+    fn should_keep_unparseable_timestamps_as_attribute() {
+        let event: EventLog =
+            serde_json::from_str(r#"{"message": "hello", "timestamp": "17/Oct/2024"}"#).unwrap();
+        assert_eq!(event.timestamp, None);
+        assert_eq!(event.attributes["timestamp"].as_text(), Some("17/Oct/2024"));
+
+        let event = event.with_timestamp(crate::event::timestamp::now());
+        let encoded = serde_json::to_value(&event).unwrap();
+        assert_eq!(encoded["timestamp"], "17/Oct/2024");
+        assert!(encoded[TIMESTAMP_FIELD].is_string());
+        let decoded: EventLog = serde_json::from_value(encoded).unwrap();
+        assert_eq!(decoded, event);
+    }
+
+    #[test]
+    fn should_read_legacy_timestamps() {
+        let event: EventLog =
+            serde_json::from_str(r#"{"message": "hello", "timestamp": 1729144611817}"#).unwrap();
+        assert_eq!(
+            event.timestamp.map(|value| value.timestamp_millis()),
+            Some(1729144611817)
+        );
+        assert!(event.attributes.is_empty());
+
+        let event: EventLog =
+            serde_json::from_str(r#"{"message": "hello", "timestamp": null}"#).unwrap();
+        assert_eq!(event.timestamp, None);
+        assert!(event.attributes.is_empty());
     }
 }
@@ -0,0 +1,26 @@
+use super::timestamp::Timestamp;
+use crate::components::name::ComponentName;
+
+/// Information about where an event comes from, carried alongside its content.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct EventMetadata {
+    /// When the event has been received by its source
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "super::timestamp::optional"
+    )]
+    pub received_at: Option<Timestamp>,
+    /// Source component that produced the event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<ComponentName>,
+    /// Transforms the event went through, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hops: Vec<ComponentName>,
+}
+
+impl EventMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.received_at.is_none() && self.source.is_none() && self.hops.is_empty()
+    }
+}
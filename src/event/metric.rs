@@ -1,8 +1,11 @@
+use std::collections::BTreeSet;
+
 use indexmap::IndexMap;
 
+use super::metadata::EventMetadata;
 use super::CowStr;
 
-#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
 pub struct EventMetricName {
     pub namespace: CowStr,
     pub name: CowStr,
@@ -10,7 +13,7 @@ pub struct EventMetricName {
 
 impl std::fmt::Display for EventMetricName {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.namespace.len() > 0 {
+        if !self.namespace.is_empty() {
             write!(f, "{}.{}", self.namespace, self.name)
         } else {
             f.write_str(self.name.as_ref())
@@ -36,6 +39,16 @@ pub struct EventMetricHeader {
     pub tags: EventMetricTags,
 }
 
+/// Consistent with the equality, which ignores the order of the tags.
+impl std::hash::Hash for EventMetricHeader {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        let mut tags = self.tags.iter().collect::<Vec<_>>();
+        tags.sort();
+        tags.hash(state);
+    }
+}
+
 impl EventMetricHeader {
     pub fn add_tag<N: Into<CowStr>, V: Into<CowStr>>(&mut self, name: N, value: V) {
         self.tags.insert(name.into(), value.into());
@@ -56,20 +69,93 @@ impl EventMetricHeader {
     }
 }
 
+/// Number of samples lower or equal to the upper limit and greater than the previous limit.
+#[derive(Debug, Clone, PartialEq, PartialOrd, serde::Deserialize, serde::Serialize)]
+pub struct HistogramBucket {
+    pub upper_limit: f64,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd, serde::Deserialize, serde::Serialize)]
+pub struct SummaryQuantile {
+    /// Between 0 and 1
+    pub quantile: f64,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd, serde::Deserialize, serde::Serialize)]
+pub struct DistributionSample {
+    pub value: f64,
+    /// How many samples this one stands for, when the emitter only sent a part of them
+    #[serde(default = "DistributionSample::default_rate")]
+    pub rate: u32,
+}
+
+impl DistributionSample {
+    const fn default_rate() -> u32 {
+        1
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, PartialOrd, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "kebab-case", tag = "type", content = "value")]
 pub enum EventMetricValue {
     Counter(u64),
     Gauge(f64),
+    /// Samples already aggregated in buckets
+    Histogram {
+        buckets: Vec<HistogramBucket>,
+        count: u64,
+        sum: f64,
+    },
+    /// Samples already aggregated in quantiles
+    Summary {
+        quantiles: Vec<SummaryQuantile>,
+        count: u64,
+        sum: f64,
+    },
+    /// Raw samples, aggregated by the sinks
+    Distribution {
+        samples: Vec<DistributionSample>,
+    },
+    /// Unique values, like the users seen in an interval
+    Set {
+        values: BTreeSet<String>,
+    },
 }
 
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+/// Whether a value is a total since a given point or a change since the previous event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventMetricKind {
+    Absolute,
+    Incremental,
+}
+
+impl EventMetricValue {
+    /// Kind of the metrics not specifying it, gauges and summaries being observations
+    /// while the other values are accumulated by the sinks.
+    pub fn default_kind(&self) -> EventMetricKind {
+        match self {
+            Self::Gauge(_) | Self::Summary { .. } => EventMetricKind::Absolute,
+            _ => EventMetricKind::Incremental,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
 pub struct EventMetric {
     #[serde(default = "crate::helper::now")]
     pub timestamp: u64,
     #[serde(flatten)]
     pub header: EventMetricHeader,
     pub value: EventMetricValue,
+    /// Depends on the value when not specified, see [`EventMetricValue::default_kind`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<EventMetricKind>,
+    /// Carried next to the content of the event, see [`super::Event`]
+    #[serde(skip)]
+    pub metadata: EventMetadata,
 }
 
 impl EventMetric {
@@ -83,9 +169,20 @@ impl EventMetric {
             timestamp,
             header: EventMetricHeader::new(namespace, name),
             value,
+            kind: None,
+            metadata: EventMetadata::default(),
         }
     }
 
+    pub fn kind(&self) -> EventMetricKind {
+        self.kind.unwrap_or_else(|| self.value.default_kind())
+    }
+
+    pub fn with_kind(mut self, kind: EventMetricKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
     pub fn with_tags_mutation<F>(mut self, callback: F) -> Self
     where
         F: Fn(&mut EventMetricTags),
@@ -99,7 +196,7 @@ impl EventMetric {
     }
 
     pub fn with_tag<N: Into<CowStr>, V: Into<CowStr>>(mut self, name: N, value: V) -> Self {
-        self.header.add_tag(name, value);
+        self.header = self.header.with_tag(name, value);
         self
     }
 
@@ -107,3 +204,67 @@ impl EventMetric {
         self.header.add_tag(name, value);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{DistributionSample, EventMetricValue, HistogramBucket};
+
+    #[test]
+    fn should_deserialize_histogram() {
+        let value: EventMetricValue = serde_json::from_str(
+            r#"{"type": "histogram", "value": {"buckets": [{"upper_limit": 0.1, "count": 3}, {"upper_limit": 1.0, "count": 1}], "count": 4, "sum": 1.2}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            EventMetricValue::Histogram {
+                buckets: vec![
+                    HistogramBucket {
+                        upper_limit: 0.1,
+                        count: 3
+                    },
+                    HistogramBucket {
+                        upper_limit: 1.0,
+                        count: 1
+                    },
+                ],
+                count: 4,
+                sum: 1.2,
+            }
+        );
+    }
+
+    #[test]
+    fn should_default_sample_rate() {
+        let value: EventMetricValue = serde_json::from_str(
+            r#"{"type": "distribution", "value": {"samples": [{"value": 12.5}, {"value": 3.0, "rate": 10}]}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            EventMetricValue::Distribution {
+                samples: vec![
+                    DistributionSample {
+                        value: 12.5,
+                        rate: 1
+                    },
+                    DistributionSample {
+                        value: 3.0,
+                        rate: 10
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn should_serialize_set() {
+        let value = EventMetricValue::Set {
+            values: ["bob".to_string(), "alice".to_string()].into(),
+        };
+        assert_eq!(
+            serde_json::to_value(&value).unwrap(),
+            serde_json::json!({"type": "set", "value": {"values": ["alice", "bob"]}})
+        );
+    }
+}
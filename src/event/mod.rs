@@ -1,17 +1,86 @@
 use std::borrow::Cow;
 
 pub mod log;
+pub mod metadata;
 pub mod metric;
+pub mod naming;
+pub mod path;
+pub mod timestamp;
+
+use self::metadata::EventMetadata;
 
 pub type CowStr = Cow<'static, str>;
 
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
-#[serde(rename_all = "snake_case", tag = "type", content = "content")]
+/// Serialized as `{"type": "log", "content": {..}, "metadata": {..}}`, the metadata being
+/// omitted when empty so that the events written by previous versions can still be read.
+#[derive(Debug, Clone, PartialEq)]
 pub enum Event {
     Log(log::EventLog),
     Metric(metric::EventMetric),
 }
 
+#[derive(serde::Serialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "content")]
+enum ContentRef<'a> {
+    Log(&'a log::EventLog),
+    Metric(&'a metric::EventMetric),
+}
+
+#[derive(serde::Serialize)]
+struct EnvelopeRef<'a> {
+    #[serde(flatten)]
+    content: ContentRef<'a>,
+    #[serde(skip_serializing_if = "EventMetadata::is_empty")]
+    metadata: &'a EventMetadata,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "content")]
+enum Content {
+    Log(log::EventLog),
+    Metric(metric::EventMetric),
+}
+
+#[derive(serde::Deserialize)]
+struct Envelope {
+    #[serde(flatten)]
+    content: Content,
+    #[serde(default)]
+    metadata: EventMetadata,
+}
+
+impl serde::Serialize for Event {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let content = match self {
+            Self::Log(inner) => ContentRef::Log(inner),
+            Self::Metric(inner) => ContentRef::Metric(inner),
+        };
+        EnvelopeRef {
+            content,
+            metadata: self.metadata(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Event {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let Envelope { content, metadata } = Envelope::deserialize(deserializer)?;
+        let mut event = match content {
+            Content::Log(inner) => Self::Log(inner),
+            Content::Metric(inner) => Self::Metric(inner),
+        };
+        *event.metadata_mut() = metadata;
+        Ok(event)
+    }
+}
+
 impl From<log::EventLog> for Event {
     fn from(value: log::EventLog) -> Self {
         Self::Log(value)
@@ -25,6 +94,20 @@ impl From<metric::EventMetric> for Event {
 }
 
 impl Event {
+    pub fn metadata(&self) -> &EventMetadata {
+        match self {
+            Self::Log(inner) => &inner.metadata,
+            Self::Metric(inner) => &inner.metadata,
+        }
+    }
+
+    pub fn metadata_mut(&mut self) -> &mut EventMetadata {
+        match self {
+            Self::Log(inner) => &mut inner.metadata,
+            Self::Metric(inner) => &mut inner.metadata,
+        }
+    }
+
     pub fn as_event_log(&self) -> Option<&log::EventLog> {
         match self {
             Self::Log(ref inner) => Some(inner),
@@ -46,6 +129,7 @@ impl Event {
         }
     }
 
+    #[cfg(test)]
     pub fn into_event_metric(self) -> Option<metric::EventMetric> {
         match self {
             Self::Metric(inner) => Some(inner),
@@ -53,3 +137,54 @@ impl Event {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::log::EventLog;
+    use super::Event;
+    use crate::components::name::ComponentName;
+
+    #[test]
+    fn should_read_events_without_metadata() {
+        let event: Event =
+            serde_json::from_str(r#"{"type":"log","content":{"message":"hello","foo":"bar"}}"#)
+                .unwrap();
+        assert_eq!(
+            event,
+            EventLog::new("hello").with_attribute("foo", "bar").into()
+        );
+        assert!(event.metadata().is_empty());
+        // events without metadata are written as before
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"type":"log","content":{"foo":"bar","message":"hello"}}"#
+        );
+    }
+
+    #[test]
+    fn should_read_timestamp_attribute_of_previous_versions() {
+        let event: Event = serde_json::from_str(
+            r#"{"type":"log","content":{"message":"hello","timestamp":1729144611}}"#,
+        )
+        .unwrap();
+        let event = event.into_event_log().unwrap();
+        assert_eq!(
+            event.timestamp,
+            Some("2024-10-17T05:56:51Z".parse().unwrap())
+        );
+        assert!(event.attributes.is_empty());
+    }
+
+    #[test]
+    fn should_keep_metadata_next_to_the_content() {
+        let mut event: Event = EventLog::new("hello").with_source("generator").into();
+        event.metadata_mut().received_at = Some("2024-10-17T05:56:51.5Z".parse().unwrap());
+        event.metadata_mut().hops.push(ComponentName::new("router"));
+        let encoded = serde_json::to_string(&event).unwrap();
+        assert_eq!(
+            encoded,
+            r#"{"type":"log","content":{"message":"hello"},"metadata":{"received_at":"2024-10-17T05:56:51.500Z","source":"generator","hops":["router"]}}"#
+        );
+        assert_eq!(serde_json::from_str::<Event>(&encoded).unwrap(), event);
+    }
+}
@@ -0,0 +1,195 @@
+use indexmap::IndexMap;
+
+use super::metric::{EventMetricName, EventMetricValue};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameCase {
+    /// Keeps the names as they are
+    Preserve,
+    Lower,
+    /// Splits the words of camel case names, like `cpuUsage` into `cpu_usage`
+    Snake,
+}
+
+/// Overrides of the naming policy of a sink, the missing fields keeping the policy of the sink.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
+pub struct Config {
+    /// Put between the namespace and the name
+    separator: Option<String>,
+    /// Replaces the characters Prometheus doesn't accept by underscores
+    sanitize: Option<bool>,
+    case: Option<NameCase>,
+    /// Units appended to the names after an underscore, keyed by metric like
+    /// `host.system.memory-used`
+    #[serde(default)]
+    units: IndexMap<String, String>,
+    /// Appended to the counters after an underscore, like `total`
+    counter_suffix: Option<String>,
+}
+
+impl Config {
+    pub fn build(self, base: MetricNaming) -> MetricNaming {
+        let mut units = base.units;
+        units.extend(self.units);
+        MetricNaming {
+            separator: self.separator.unwrap_or(base.separator),
+            sanitize: self.sanitize.unwrap_or(base.sanitize),
+            case: self.case.unwrap_or(base.case),
+            units,
+            counter_suffix: self.counter_suffix.or(base.counter_suffix),
+        }
+    }
+}
+
+/// How a sink renders the name of a metric.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricNaming {
+    separator: String,
+    sanitize: bool,
+    case: NameCase,
+    units: IndexMap<String, String>,
+    counter_suffix: Option<String>,
+}
+
+/// Same rendering as the display of the name.
+impl Default for MetricNaming {
+    fn default() -> Self {
+        Self {
+            separator: String::from("."),
+            sanitize: false,
+            case: NameCase::Preserve,
+            units: IndexMap::new(),
+            counter_suffix: None,
+        }
+    }
+}
+
+impl MetricNaming {
+    /// Names following the Prometheus conventions, like `host_system_memory_used_total`.
+    pub fn prometheus() -> Self {
+        Self {
+            separator: String::from("_"),
+            sanitize: true,
+            case: NameCase::Snake,
+            units: IndexMap::new(),
+            counter_suffix: Some(String::from("total")),
+        }
+    }
+}
+
+fn convert_case(value: &str, case: NameCase) -> String {
+    match case {
+        NameCase::Preserve => value.to_string(),
+        NameCase::Lower => value.to_lowercase(),
+        NameCase::Snake => {
+            let mut result = String::with_capacity(value.len());
+            let mut previous = None::<char>;
+            for character in value.chars() {
+                if character.is_uppercase()
+                    && previous.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit())
+                {
+                    result.push('_');
+                }
+                result.extend(character.to_lowercase());
+                previous = Some(character);
+            }
+            result
+        }
+    }
+}
+
+/// Prometheus names match `[a-zA-Z_:][a-zA-Z0-9_:]*`.
+fn sanitize(value: &str) -> String {
+    let mut result = value
+        .chars()
+        .map(
+            |c| match c.is_ascii_alphanumeric() || c == '_' || c == ':' {
+                true => c,
+                false => '_',
+            },
+        )
+        .collect::<String>();
+    if result.starts_with(|c: char| c.is_ascii_digit()) {
+        result.insert(0, '_');
+    }
+    result
+}
+
+impl EventMetricName {
+    pub fn render(&self, naming: &MetricNaming, value: &EventMetricValue) -> String {
+        let mut result = convert_case(&self.name, naming.case);
+        if !self.namespace.is_empty() {
+            result = format!(
+                "{}{}{result}",
+                convert_case(&self.namespace, naming.case),
+                naming.separator
+            );
+        }
+        let suffixes = naming
+            .units
+            .get(self.to_string().as_str())
+            .into_iter()
+            .chain(match value {
+                EventMetricValue::Counter(_) => naming.counter_suffix.as_ref(),
+                _ => None,
+            });
+        for suffix in suffixes {
+            let suffix = format!("_{suffix}");
+            if !result.ends_with(&suffix) {
+                result.push_str(&suffix);
+            }
+        }
+        match naming.sanitize {
+            true => sanitize(&result),
+            false => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::{Config, MetricNaming};
+    use crate::event::metric::{EventMetricName, EventMetricValue};
+
+    #[test_case("host.system", "global-cpu-usage", EventMetricValue::Gauge(1.0), "host_system_global_cpu_usage"; "sysinfo gauge")]
+    #[test_case("", "requestsCount", EventMetricValue::Counter(1), "requests_count_total"; "camel case counter")]
+    #[test_case("tiny-vector", "events_in_total", EventMetricValue::Counter(1), "tiny_vector_events_in_total"; "existing suffix")]
+    #[test_case("", "5xx", EventMetricValue::Counter(1), "_5xx_total"; "leading digit")]
+    #[test_case("host.system", "memory-used", EventMetricValue::Gauge(1.0), "host_system_memory_used_bytes"; "unit")]
+    fn should_render_prometheus_names(
+        namespace: &str,
+        name: &str,
+        value: EventMetricValue,
+        expected: &str,
+    ) {
+        let config: Config =
+            toml::from_str(r#"units = { "host.system.memory-used" = "bytes" }"#).unwrap();
+        let naming = config.build(MetricNaming::prometheus());
+        let name = EventMetricName::new(namespace.to_string(), name.to_string());
+        assert_eq!(name.render(&naming, &value), expected);
+    }
+
+    #[test]
+    fn should_override_policy() {
+        let config: Config = toml::from_str(
+            r#"
+separator = ":"
+counter_suffix = "count"
+"#,
+        )
+        .unwrap();
+        let naming = config.build(MetricNaming::prometheus());
+        let name = EventMetricName::new("http", "requests.Failed");
+        assert_eq!(
+            name.render(&naming, &EventMetricValue::Counter(1)),
+            "http:requests_failed_count"
+        );
+        assert_eq!(
+            name.render(&MetricNaming::default(), &EventMetricValue::Counter(1)),
+            name.to_string()
+        );
+    }
+}
@@ -0,0 +1,364 @@
+use std::str::FromStr;
+
+use super::log::{EventLog, EventLogAttribute};
+use super::CowStr;
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum PathError {
+    #[error("the path is empty")]
+    Empty,
+    #[error("unexpected character {character:?} at position {position} in path {path:?}")]
+    UnexpectedCharacter {
+        path: String,
+        character: char,
+        position: usize,
+    },
+    #[error("unterminated {expected:?} in path {path:?}")]
+    Unterminated { path: String, expected: char },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Segment {
+    Field(CowStr),
+    Index(usize),
+}
+
+/// Location of an attribute nested in objects and arrays, like `http.request.headers[0]`.
+///
+/// Field names containing a dot or a bracket can be quoted, like `"http.method"`. A log
+/// having an attribute named like the whole path, like `host.name` received from a flat
+/// json object, gets it used instead of the nested one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttributePath {
+    /// Starts with a field, the attributes of a log being named
+    segments: Vec<Segment>,
+    /// The path as written
+    text: String,
+}
+
+impl FromStr for AttributePath {
+    type Err = PathError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let unexpected = |position: usize, character: char| PathError::UnexpectedCharacter {
+            path: path.to_string(),
+            character,
+            position,
+        };
+        let mut segments = Vec::new();
+        let mut chars = path.char_indices().peekable();
+        // a field is expected at the start and after each dot
+        let mut expect_field = true;
+        while let Some((position, character)) = chars.next() {
+            match character {
+                '"' if expect_field => {
+                    let mut field = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '"')) => break,
+                            Some((_, '\\')) => match chars.next() {
+                                Some((_, escaped)) => field.push(escaped),
+                                None => break,
+                            },
+                            Some((_, other)) => field.push(other),
+                            None => {
+                                return Err(PathError::Unterminated {
+                                    path: path.to_string(),
+                                    expected: '"',
+                                })
+                            }
+                        }
+                    }
+                    segments.push(Segment::Field(field.into()));
+                    expect_field = false;
+                }
+                '.' | '[' | ']' if expect_field => return Err(unexpected(position, character)),
+                '.' => expect_field = true,
+                '[' => {
+                    let mut index = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, ']')) => break,
+                            Some((_, digit)) if digit.is_ascii_digit() => index.push(digit),
+                            Some((position, other)) => return Err(unexpected(position, other)),
+                            None => {
+                                return Err(PathError::Unterminated {
+                                    path: path.to_string(),
+                                    expected: ']',
+                                })
+                            }
+                        }
+                    }
+                    let index = index.parse().map_err(|_| unexpected(position, ']'))?;
+                    segments.push(Segment::Index(index));
+                }
+                _ if expect_field => {
+                    let mut field = String::from(character);
+                    while let Some((_, next)) = chars.next_if(|(_, c)| !matches!(c, '.' | '[')) {
+                        field.push(next);
+                    }
+                    segments.push(Segment::Field(field.into()));
+                    expect_field = false;
+                }
+                other => return Err(unexpected(position, other)),
+            }
+        }
+        if segments.is_empty() {
+            return Err(PathError::Empty);
+        }
+        if expect_field {
+            return Err(unexpected(path.len(), '.'));
+        }
+        if !matches!(segments.first(), Some(Segment::Field(_))) {
+            return Err(unexpected(0, '['));
+        }
+        Ok(Self {
+            segments,
+            text: path.to_string(),
+        })
+    }
+}
+
+impl AttributePath {
+    fn split(&self) -> (&CowStr, &[Segment]) {
+        match self.segments.split_first() {
+            Some((Segment::Field(name), rest)) => (name, rest),
+            // enforced when parsing
+            _ => unreachable!("a path starts with a field"),
+        }
+    }
+
+    /// Name of the top level attribute matching the whole path, for the nested paths.
+    fn flat_name(&self) -> Option<&str> {
+        (self.segments.len() > 1).then_some(self.text.as_str())
+    }
+}
+
+fn child<'a>(value: &'a EventLogAttribute, segment: &Segment) -> Option<&'a EventLogAttribute> {
+    match (value, segment) {
+        (EventLogAttribute::Map(inner), Segment::Field(name)) => inner.get(name.as_ref()),
+        (EventLogAttribute::Array(inner), Segment::Index(index)) => inner.get(*index),
+        _ => None,
+    }
+}
+
+fn child_mut<'a>(
+    value: &'a mut EventLogAttribute,
+    segment: &Segment,
+) -> Option<&'a mut EventLogAttribute> {
+    match (value, segment) {
+        (EventLogAttribute::Map(inner), Segment::Field(name)) => inner.get_mut(name.as_ref()),
+        (EventLogAttribute::Array(inner), Segment::Index(index)) => inner.get_mut(*index),
+        _ => None,
+    }
+}
+
+impl EventLog {
+    pub fn get_path(&self, path: &AttributePath) -> Option<&EventLogAttribute> {
+        if let Some(value) = path.flat_name().and_then(|name| self.attributes.get(name)) {
+            return Some(value);
+        }
+        let (name, rest) = path.split();
+        rest.iter()
+            .try_fold(self.attributes.get(name.as_ref())?, child)
+    }
+
+    /// Sets the attribute, creating the missing objects on the way. Returns false when an
+    /// array index is out of bounds or when a parent isn't an object or an array.
+    pub fn insert_path(&mut self, path: &AttributePath, value: EventLogAttribute) -> bool {
+        if let Some(current) = path
+            .flat_name()
+            .and_then(|name| self.attributes.get_mut(name))
+        {
+            *current = value;
+            return true;
+        }
+        let (name, rest) = path.split();
+        let Some((last, parents)) = rest.split_last() else {
+            self.attributes.insert(name.clone(), value);
+            return true;
+        };
+        let mut current = self
+            .attributes
+            .entry(name.clone())
+            .or_insert_with(|| EventLogAttribute::Map(Default::default()));
+        for segment in parents {
+            current = match (current, segment) {
+                (EventLogAttribute::Map(inner), Segment::Field(name)) => inner
+                    .entry(name.clone())
+                    .or_insert_with(|| EventLogAttribute::Map(Default::default())),
+                (EventLogAttribute::Array(inner), Segment::Index(index)) => {
+                    match inner.get_mut(*index) {
+                        Some(item) => item,
+                        None => return false,
+                    }
+                }
+                _ => return false,
+            };
+        }
+        match (current, last) {
+            (EventLogAttribute::Map(inner), Segment::Field(name)) => {
+                inner.insert(name.clone(), value);
+                true
+            }
+            (EventLogAttribute::Array(inner), Segment::Index(index)) if *index < inner.len() => {
+                inner[*index] = value;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn remove_path(&mut self, path: &AttributePath) -> Option<EventLogAttribute> {
+        if let Some(value) = path
+            .flat_name()
+            .and_then(|name| self.attributes.shift_remove(name))
+        {
+            return Some(value);
+        }
+        let (name, rest) = path.split();
+        let Some((last, parents)) = rest.split_last() else {
+            return self.attributes.shift_remove(name.as_ref());
+        };
+        let mut current = self.attributes.get_mut(name.as_ref())?;
+        for segment in parents {
+            current = child_mut(current, segment)?;
+        }
+        match (current, last) {
+            (EventLogAttribute::Map(inner), Segment::Field(name)) => {
+                inner.shift_remove(name.as_ref())
+            }
+            (EventLogAttribute::Array(inner), Segment::Index(index)) if *index < inner.len() => {
+                Some(inner.remove(*index))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::{AttributePath, PathError, Segment};
+    use crate::event::log::{EventLog, EventLogAttribute};
+
+    fn path(value: &str) -> AttributePath {
+        value.parse().unwrap()
+    }
+
+    #[test_case("message", &[Segment::Field("message".into())]; "single field")]
+    #[test_case("http.request.headers[0]", &[
+        Segment::Field("http".into()),
+        Segment::Field("request".into()),
+        Segment::Field("headers".into()),
+        Segment::Index(0),
+    ]; "nested")]
+    #[test_case("matrix[1][12].value", &[
+        Segment::Field("matrix".into()),
+        Segment::Index(1),
+        Segment::Index(12),
+        Segment::Field("value".into()),
+    ]; "nested arrays")]
+    #[test_case(r#""http.method".value"#, &[
+        Segment::Field("http.method".into()),
+        Segment::Field("value".into()),
+    ]; "quoted field")]
+    fn should_parse(value: &str, expected: &[Segment]) {
+        assert_eq!(path(value).segments, expected);
+    }
+
+    #[test_case(""; "empty")]
+    #[test_case("[0]"; "starting with index")]
+    #[test_case("http..method"; "empty field")]
+    #[test_case("http."; "trailing dot")]
+    #[test_case("headers[a]"; "invalid index")]
+    #[test_case("headers[0"; "unterminated index")]
+    #[test_case(r#""http"#; "unterminated quote")]
+    fn shouldnt_parse(value: &str) {
+        assert!(value.parse::<AttributePath>().is_err());
+    }
+
+    #[test]
+    fn should_report_position() {
+        assert_eq!(
+            "http..method".parse::<AttributePath>().unwrap_err(),
+            PathError::UnexpectedCharacter {
+                path: "http..method".into(),
+                character: '.',
+                position: 5,
+            }
+        );
+    }
+
+    fn event() -> EventLog {
+        serde_json::from_str(
+            r#"{"message": "hello", "http": {"request": {"headers": ["accept", "host"]}}}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn should_get_nested_attributes() {
+        let event = event();
+        assert_eq!(
+            event.get_path(&path("http.request.headers[1]")),
+            Some(&EventLogAttribute::from("host"))
+        );
+        assert!(event.get_path(&path("http.request.headers[2]")).is_none());
+        assert!(event.get_path(&path("http.response")).is_none());
+        assert!(event.get_path(&path("http[0]")).is_none());
+    }
+
+    #[test]
+    fn should_insert_nested_attributes() {
+        let mut event = event();
+        assert!(event.insert_path(&path("http.response.status"), 200u64.into()));
+        assert!(event.insert_path(&path("http.request.headers[0]"), "accept-encoding".into()));
+        assert!(!event.insert_path(&path("http.request.headers[2]"), "cookie".into()));
+        assert!(!event.insert_path(&path("http.request.headers.first"), "cookie".into()));
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({
+                "message": "hello",
+                "http": {
+                    "request": {"headers": ["accept-encoding", "host"]},
+                    "response": {"status": 200},
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn should_remove_nested_attributes() {
+        let mut event = event();
+        assert_eq!(
+            event.remove_path(&path("http.request.headers[0]")),
+            Some("accept".into())
+        );
+        assert!(event.remove_path(&path("http.response")).is_none());
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({"message": "hello", "http": {"request": {"headers": ["host"]}}})
+        );
+    }
+
+    #[test]
+    fn should_use_flat_attributes() {
+        let mut event: EventLog = serde_json::from_str(
+            r#"{"message": "hello", "host.name": "a", "host": {"name": "b"}}"#,
+        )
+        .unwrap();
+        let path = path("host.name");
+        assert_eq!(event.get_path(&path), Some(&EventLogAttribute::from("a")));
+        assert!(event.insert_path(&path, "c".into()));
+        assert_eq!(event.remove_path(&path), Some("c".into()));
+        // the nested attribute once the flat one is gone
+        assert_eq!(event.get_path(&path), Some(&EventLogAttribute::from("b")));
+        assert!(event.insert_path(&path, "d".into()));
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({"message": "hello", "host": {"name": "d"}})
+        );
+    }
+}
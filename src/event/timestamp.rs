@@ -0,0 +1,237 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+
+/// Point in time with a nanosecond precision.
+pub type Timestamp = DateTime<Utc>;
+
+pub fn now() -> Timestamp {
+    Utc::now()
+}
+
+/// Parses a text with a strftime format, the timestamp being considered in UTC when
+/// the format has no offset. Without format, the text is parsed as ISO8601.
+pub fn parse(value: &str, format: Option<&str>) -> Option<Timestamp> {
+    let Some(format) = format else {
+        return value
+            .parse::<DateTime<FixedOffset>>()
+            .map(|parsed| parsed.to_utc())
+            .or_else(|_| {
+                value
+                    .parse::<NaiveDateTime>()
+                    .map(|parsed| parsed.and_utc())
+            })
+            .ok();
+    };
+    DateTime::parse_from_str(value, format)
+        .map(|parsed| parsed.to_utc())
+        .or_else(|_| NaiveDateTime::parse_from_str(value, format).map(|parsed| parsed.and_utc()))
+        .or_else(|_| {
+            NaiveDate::parse_from_str(value, format)
+                .map(|parsed| parsed.and_time(Default::default()).and_utc())
+        })
+        .ok()
+}
+
+/// Seconds since epoch above which an integer is considered in a smaller unit, 1e11
+/// seconds being around the year 5138.
+const MAX_EPOCH_SECONDS: i64 = 100_000_000_000;
+
+/// Converts a time since epoch, guessing its unit from its scale: seconds, then
+/// milliseconds, microseconds and nanoseconds.
+pub fn from_epoch(value: i64) -> Option<Timestamp> {
+    let mut divisor = 1;
+    while divisor < 1_000_000_000 && value.unsigned_abs() / divisor >= MAX_EPOCH_SECONDS as u64 {
+        divisor *= 1000;
+    }
+    let divisor = divisor as i64;
+    let nanos = value.rem_euclid(divisor) * (1_000_000_000 / divisor);
+    Timestamp::from_timestamp(value.div_euclid(divisor), nanos as u32)
+}
+
+/// Same as [`from_epoch`] for a time with a fractional part, kept to the microsecond.
+pub fn from_epoch_f64(value: f64) -> Option<Timestamp> {
+    if !value.is_finite() {
+        return None;
+    }
+    let mut value = value;
+    let mut unit = 1;
+    while unit < 1_000_000_000 && value.abs() >= MAX_EPOCH_SECONDS as f64 {
+        value /= 1000.0;
+        unit *= 1000;
+    }
+    // a float of a recent time in seconds is only precise to the microsecond
+    let micros = (value.fract().abs() * 1e6).round().min(999_999.0) as u32;
+    let micros = if value < 0.0 && micros > 0 {
+        1_000_000 - micros
+    } else {
+        micros
+    };
+    Timestamp::from_timestamp(value.floor() as i64, micros * 1000)
+}
+
+/// How a timestamp gets written by the sinks encoding the events.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampFormat {
+    /// Text like `2024-10-17T05:56:51.817813Z`
+    #[default]
+    Rfc3339,
+    /// Seconds since epoch
+    Unix,
+    /// Milliseconds since epoch
+    UnixMs,
+    /// Nanoseconds since epoch
+    UnixNs,
+}
+
+impl TimestampFormat {
+    pub fn encode(&self, timestamp: &Timestamp) -> serde_json::Value {
+        match self {
+            Self::Rfc3339 => timestamp
+                .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+                .into(),
+            Self::Unix => timestamp.timestamp().into(),
+            Self::UnixMs => timestamp.timestamp_millis().into(),
+            Self::UnixNs => timestamp.timestamp_nanos_opt().unwrap_or(i64::MAX).into(),
+        }
+    }
+}
+
+/// Serde helpers for an optional timestamp, written as RFC3339 and read from RFC3339 or
+/// from a time since epoch, like the `timestamp` attribute of the previous versions. The
+/// unit of the time since epoch is guessed with [`super::from_epoch`].
+pub mod optional {
+    use serde::de::Error;
+
+    use super::Timestamp;
+
+    pub fn serialize<S>(value: &Option<Timestamp>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match value {
+            Some(timestamp) => serializer
+                .serialize_str(&timestamp.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    struct Visitor;
+
+    impl<'de> serde::de::Visitor<'de> for Visitor {
+        type Value = Option<Timestamp>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("an RFC3339 timestamp or a time since epoch")
+        }
+
+        fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+            super::parse(value, None)
+                .map(Some)
+                .ok_or_else(|| E::custom(format!("invalid timestamp {value:?}")))
+        }
+
+        fn visit_i64<E: Error>(self, value: i64) -> Result<Self::Value, E> {
+            super::from_epoch(value)
+                .map(Some)
+                .ok_or_else(|| E::custom(format!("timestamp {value} out of range")))
+        }
+
+        fn visit_u64<E: Error>(self, value: u64) -> Result<Self::Value, E> {
+            let value = i64::try_from(value).map_err(E::custom)?;
+            self.visit_i64(value)
+        }
+
+        fn visit_f64<E: Error>(self, value: f64) -> Result<Self::Value, E> {
+            super::from_epoch_f64(value)
+                .map(Some)
+                .ok_or_else(|| E::custom(format!("timestamp {value} out of range")))
+        }
+
+        fn visit_none<E: Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            deserializer.deserialize_any(self)
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Timestamp>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::{from_epoch, parse, Timestamp, TimestampFormat};
+
+    fn timestamp(value: &str) -> Timestamp {
+        value.parse().unwrap()
+    }
+
+    #[test_case("2024-10-17T05:56:51.817813Z", None, "2024-10-17T05:56:51.817813Z"; "rfc3339")]
+    #[test_case("2024-10-17T07:56:51+02:00", None, "2024-10-17T05:56:51Z"; "iso8601 with offset")]
+    #[test_case("2024-10-17T05:56:51.000000123", None, "2024-10-17T05:56:51.000000123Z"; "iso8601 without offset")]
+    #[test_case("17/Oct/2024:07:56:51 +0200", Some("%d/%b/%Y:%H:%M:%S %z"), "2024-10-17T05:56:51Z"; "format with offset")]
+    #[test_case("2024-10-17 05:56:51", Some("%Y-%m-%d %H:%M:%S"), "2024-10-17T05:56:51Z"; "format without offset")]
+    #[test_case("17.10.2024", Some("%d.%m.%Y"), "2024-10-17T00:00:00Z"; "date only")]
+    fn should_parse(value: &str, format: Option<&str>, expected: &str) {
+        assert_eq!(parse(value, format), Some(timestamp(expected)));
+    }
+
+    #[test]
+    fn should_not_parse_invalid_value() {
+        assert_eq!(parse("yesterday", None), None);
+        assert_eq!(parse("2024-10-17", Some("%H:%M")), None);
+    }
+
+    #[test_case(1729144611, "2024-10-17T05:56:51Z"; "seconds")]
+    #[test_case(1729144611817, "2024-10-17T05:56:51.817Z"; "milliseconds")]
+    #[test_case(1729144611817813, "2024-10-17T05:56:51.817813Z"; "microseconds")]
+    #[test_case(1729144611817813000, "2024-10-17T05:56:51.817813Z"; "nanoseconds")]
+    #[test_case(-1500, "1969-12-31T23:35:00Z"; "before epoch")]
+    fn should_guess_epoch_unit(value: i64, expected: &str) {
+        assert_eq!(from_epoch(value), Some(timestamp(expected)));
+    }
+
+    #[test_case("1729144611.5", "2024-10-17T05:56:51.5Z"; "seconds")]
+    #[test_case("1729144611817.0", "2024-10-17T05:56:51.817Z"; "milliseconds")]
+    fn should_deserialize_float_epoch(value: &str, expected: &str) {
+        let mut deserializer = serde_json::Deserializer::from_str(value);
+        let parsed = super::optional::deserialize(&mut deserializer).unwrap();
+        assert_eq!(parsed, Some(timestamp(expected)));
+    }
+
+    #[test]
+    fn should_encode() {
+        let value = timestamp("2024-10-17T05:56:51.000000123Z");
+        assert_eq!(
+            TimestampFormat::Rfc3339.encode(&value),
+            serde_json::json!("2024-10-17T05:56:51.000000123Z")
+        );
+        assert_eq!(
+            TimestampFormat::Unix.encode(&value),
+            serde_json::json!(1729144611)
+        );
+        assert_eq!(
+            TimestampFormat::UnixMs.encode(&value),
+            serde_json::json!(1729144611000_i64)
+        );
+        assert_eq!(
+            TimestampFormat::UnixNs.encode(&value),
+            serde_json::json!(1729144611000000123_i64)
+        );
+    }
+}
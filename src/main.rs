@@ -1,3 +1,12 @@
+// Some of the core helpers are only used by the optional components.
+#![cfg_attr(not(feature = "default"), allow(dead_code))]
+
+use std::process::ExitCode;
+
+use clap::Parser;
+
+mod api;
+mod cli;
 mod components;
 mod event;
 mod helper;
@@ -8,16 +17,23 @@ mod topology;
 mod transforms;
 
 fn init_tracing() {
-    if let Err(err) = tracing_subscriber::fmt().try_init() {
+    use tracing_subscriber::filter::LevelFilter;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let result = tracing_subscriber::registry()
+        .with(LevelFilter::INFO)
+        .with(tracing_subscriber::fmt::layer())
+        .with(crate::components::metrics::ErrorCounterLayer)
+        .try_init();
+    if let Err(err) = result {
         eprintln!("unable to init tracing: {err:?}");
     }
 }
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     init_tracing();
 
-    let config = crate::topology::Config::from_path("./example.toml").unwrap();
-    let topo = config.build().await.unwrap();
-    topo.start().await.unwrap().wait().await;
+    crate::cli::Args::parse().execute().await
 }
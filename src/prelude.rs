@@ -1,12 +1,13 @@
-pub type Sender = tokio::sync::mpsc::Sender<crate::event::Event>;
-pub type Receiver = tokio::sync::mpsc::Receiver<crate::event::Event>;
+pub use crate::components::buffer::{Receiver, Sender};
 
+#[cfg(test)]
 #[inline]
 pub fn create_channel(size: usize) -> (Sender, Receiver) {
-    tokio::sync::mpsc::channel(size)
+    let (sender, receiver) = crate::components::buffer::memory::channel(size, Default::default());
+    (Sender::Memory(sender), receiver.into())
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 #[serde(untagged)]
 pub enum StringOrEnv {
     String(String),
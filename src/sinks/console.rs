@@ -1,6 +1,7 @@
+use crate::components::ExecutionError;
 use crate::prelude::Receiver;
 
-#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct Config {}
 
@@ -40,11 +41,12 @@ impl super::Preparable for Sink {
 }
 
 impl super::Executable for Sink {
-    async fn execute(self, mut receiver: Receiver) {
+    async fn execute(self, mut receiver: Receiver) -> Result<(), ExecutionError> {
         tracing::info!("starting");
         while let Some(input) = receiver.recv().await {
             println!("{input:?}");
         }
         tracing::info!("stopping");
+        Ok(())
     }
 }
@@ -1,12 +1,15 @@
 use std::borrow::Cow;
 
 use reqwest::StatusCode;
+use tokio::time::Duration;
 
 use crate::prelude::{Receiver, StringOrEnv};
 
 const APPLICATION_JSON: reqwest::header::HeaderValue =
     reqwest::header::HeaderValue::from_static("application/json");
 const USER_AGENT: &str = concat!(env!("CARGO_CRATE_NAME"), " ", env!("CARGO_PKG_VERSION"));
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
 
 #[derive(Debug, thiserror::Error)]
 pub enum BuildError {
@@ -24,6 +27,15 @@ pub enum ExecutionError {
     InvalidPayload,
     #[error("request failed")]
     RequestError(#[source] reqwest::Error),
+    #[error("unexpected status code {0}")]
+    UnexpectedStatus(StatusCode),
+}
+
+impl ExecutionError {
+    /// Sending the same logs again will fail the same way.
+    fn is_permanent(&self) -> bool {
+        matches!(self, Self::InvalidPayload)
+    }
 }
 
 struct DatadogClient {
@@ -34,20 +46,16 @@ struct DatadogClient {
 impl DatadogClient {
     async fn send_many(
         &self,
-        event_logs: impl Iterator<Item = crate::event::log::EventLog>,
+        events: &[crate::event::log::EventLog],
     ) -> Result<(), ExecutionError> {
         tracing::debug!("sending logs to datadog logs");
-        let events = event_logs.collect::<Vec<_>>();
-        match self
-            .inner
-            .post(self.url.as_ref())
-            .json(&events)
-            .send()
-            .await
-        {
+        match self.inner.post(self.url.as_ref()).json(events).send().await {
             Ok(res) if res.status() == StatusCode::BAD_REQUEST => {
                 Err(ExecutionError::InvalidPayload)
             }
+            Ok(res) if !res.status().is_success() => {
+                Err(ExecutionError::UnexpectedStatus(res.status()))
+            }
             Ok(res) => {
                 tracing::debug!("events sent with status code: {:?}", res.status());
                 Ok(())
@@ -57,7 +65,15 @@ impl DatadogClient {
     }
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+/// Keeps the logs, with the time they've been received when they don't have a timestamp,
+/// otherwise datadog uses the time they reach its intake.
+fn into_datadog_log(event: crate::event::Event) -> Option<crate::event::log::EventLog> {
+    let mut event_log = event.into_event_log()?;
+    event_log.timestamp = event_log.timestamp_or_received();
+    Some(event_log)
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 pub struct Config {
     url: Option<String>,
     api_token: StringOrEnv,
@@ -107,6 +123,29 @@ impl Sink {
     pub(crate) fn flavor(&self) -> &'static str {
         "datadog_log"
     }
+
+    /// Sends the logs until datadog accepts or rejects them. The buffer acknowledges them
+    /// when the next ones are received, so they are not lost while datadog is unavailable.
+    async fn deliver(&self, events: &[crate::event::log::EventLog]) {
+        let mut backoff = INITIAL_BACKOFF;
+        loop {
+            match self.client.send_many(events).await {
+                Ok(_) => return,
+                Err(error) if error.is_permanent() => {
+                    tracing::error!(
+                        "unable to send {} logs, discarding: {error:?}",
+                        events.len()
+                    );
+                    return;
+                }
+                Err(error) => {
+                    tracing::error!("unable to send logs, retrying in {backoff:?}: {error:?}");
+                    tokio::time::sleep(backoff).await;
+                    backoff = backoff.saturating_mul(2).min(MAX_BACKOFF);
+                }
+            }
+        }
+    }
 }
 
 impl super::Preparable for Sink {
@@ -119,7 +158,10 @@ impl super::Preparable for Sink {
 }
 
 impl super::Executable for Sink {
-    async fn execute(self, mut receiver: Receiver) {
+    async fn execute(
+        self,
+        mut receiver: Receiver,
+    ) -> Result<(), crate::components::ExecutionError> {
         tracing::info!("starting");
         let mut buffer = Vec::with_capacity(20);
         loop {
@@ -128,14 +170,69 @@ impl super::Executable for Sink {
                 break;
             }
             tracing::debug!("received {size} events");
-            if let Err(error) = self
-                .client
-                .send_many(buffer.drain(..).filter_map(|item| item.into_event_log()))
-                .await
-            {
-                eprintln!("{error:?}");
+            let events = buffer
+                .drain(..)
+                .filter_map(into_datadog_log)
+                .collect::<Vec<_>>();
+            if !events.is_empty() {
+                self.deliver(&events).await;
             }
         }
         tracing::info!("stopping");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::Json;
+
+    use crate::components::buffer::WhenFull;
+    use crate::event::log::EventLog;
+    use crate::sinks::{Executable, Preparable};
+
+    type Received = Arc<Mutex<Vec<serde_json::Value>>>;
+
+    /// Fails the first request and accepts the next ones.
+    async fn flaky_intake(
+        State(received): State<Received>,
+        Json(body): Json<serde_json::Value>,
+    ) -> StatusCode {
+        let mut received = received.lock().unwrap();
+        received.push(body);
+        match received.len() {
+            1 => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::ACCEPTED,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_retry_until_delivered() {
+        let received = Received::default();
+        let router = axum::Router::new()
+            .route("/", axum::routing::post(flaky_intake))
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let config: super::Config = toml::from_str(&format!(
+            "url = \"http://{address}/\"\napi_token = \"token\""
+        ))
+        .unwrap();
+        let sink = config.build().unwrap().prepare().await.unwrap();
+        let (tx, rx) = crate::components::buffer::memory::channel(10, WhenFull::Block);
+        tx.send(EventLog::new("hello").into()).await.unwrap();
+        drop(tx);
+        sink.execute(rx.into()).await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0], received[1]);
+        assert_eq!(received[1][0]["message"], "hello");
     }
 }
@@ -2,13 +2,18 @@ use std::path::PathBuf;
 
 use tokio::io::AsyncWriteExt;
 
+use crate::components::ExecutionError;
+use crate::event::timestamp::TimestampFormat;
 use crate::event::Event;
 use crate::prelude::Receiver;
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct Config {
     path: PathBuf,
+    /// How the timestamps of the logs are written
+    #[serde(default)]
+    timestamp_format: TimestampFormat,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -17,7 +22,10 @@ pub enum BuildError {}
 impl Config {
     pub async fn build(self) -> Result<Sink, BuildError> {
         Ok(Sink {
-            state: Stale { path: self.path },
+            state: Stale {
+                path: self.path,
+                timestamp_format: self.timestamp_format,
+            },
         })
     }
 }
@@ -34,10 +42,12 @@ pub enum StartingError {
 
 pub(crate) struct Stale {
     path: PathBuf,
+    timestamp_format: TimestampFormat,
 }
 
 pub(crate) struct Running {
     output: tokio::fs::File,
+    timestamp_format: TimestampFormat,
 }
 
 pub struct Sink<S = Stale> {
@@ -62,13 +72,16 @@ impl super::Preparable for Sink<Stale> {
             .open(&self.state.path)
             .await?;
         Ok(Sink {
-            state: Running { output },
+            state: Running {
+                output,
+                timestamp_format: self.state.timestamp_format,
+            },
         })
     }
 }
 
 impl super::Executable for Sink<Running> {
-    async fn execute(mut self, mut receiver: Receiver) {
+    async fn execute(mut self, mut receiver: Receiver) -> Result<(), ExecutionError> {
         tracing::info!("starting");
         while let Some(input) = receiver.recv().await {
             if let Err(err) = self.handle(input).await {
@@ -76,14 +89,59 @@ impl super::Executable for Sink<Running> {
             }
         }
         tracing::info!("stopping");
+        if let Err(err) = self.state.output.flush().await {
+            tracing::error!("unable to flush file: {err:?}");
+        }
+        Ok(())
+    }
+}
+
+fn encode(event: &Event, timestamp_format: TimestampFormat) -> serde_json::Result<Vec<u8>> {
+    let timestamp = event.as_event_log().and_then(|inner| inner.timestamp);
+    match timestamp {
+        Some(timestamp) if timestamp_format != TimestampFormat::Rfc3339 => {
+            let mut value = serde_json::to_value(event)?;
+            if let Some(field) = value.pointer_mut("/content/@timestamp") {
+                *field = timestamp_format.encode(&timestamp);
+            }
+            serde_json::to_vec(&value)
+        }
+        _ => serde_json::to_vec(event),
     }
 }
 
 impl Sink<Running> {
     async fn handle(&mut self, event: Event) -> std::io::Result<()> {
-        let mut encoded = serde_json::to_vec(&event)?;
+        let mut encoded = encode(&event, self.state.timestamp_format)?;
         encoded.push(b'\n');
         self.state.output.write_all(&encoded).await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::encode;
+    use crate::event::log::EventLog;
+    use crate::event::timestamp::TimestampFormat;
+
+    #[test]
+    fn should_encode_timestamp_with_format() {
+        let event = EventLog::new("hello")
+            .with_timestamp("2024-10-17T05:56:51.5Z".parse().unwrap())
+            .into();
+        let encoded = encode(&event, TimestampFormat::Rfc3339).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&encoded).unwrap(),
+            r#"{"type":"log","content":{"message":"hello","@timestamp":"2024-10-17T05:56:51.500Z"}}"#
+        );
+        let encoded = encode(&event, TimestampFormat::UnixMs).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&encoded).unwrap(),
+            serde_json::json!({
+                "type": "log",
+                "content": {"message": "hello", "@timestamp": 1729144611500_i64},
+            })
+        );
+    }
+}
@@ -1,4 +1,6 @@
+use crate::components::metrics::COMPONENT_SPAN;
 use crate::components::name::ComponentName;
+use crate::components::ExecutionError;
 use crate::prelude::Receiver;
 
 pub mod black_hole;
@@ -12,7 +14,7 @@ pub mod prometheus_exporter;
 #[cfg(feature = "sink-sqlite")]
 pub mod sqlite;
 
-const COMPONENT_KIND: &str = "sink";
+pub(crate) const COMPONENT_KIND: &str = "sink";
 
 #[derive(Debug, thiserror::Error)]
 pub enum BuildError {
@@ -34,7 +36,7 @@ pub enum BuildError {
     Sqlite(#[from] sqlite::BuildError),
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 #[cfg_attr(test, derive(derive_more::From))]
 pub enum Config {
@@ -51,6 +53,22 @@ pub enum Config {
 }
 
 impl Config {
+    /// Type of the sink, as written in the configuration.
+    pub fn flavor(&self) -> &'static str {
+        match self {
+            Self::BlackHole(_) => "black_hole",
+            Self::Console(_) => "console",
+            #[cfg(feature = "sink-datadog-logs")]
+            Self::DatadogLogs(_) => "datadog_logs",
+            #[cfg(feature = "sink-file")]
+            Self::File(_) => "file",
+            #[cfg(feature = "sink-prometheus-exporter")]
+            Self::PrometheusExporter(_) => "prometheus_exporter",
+            #[cfg(feature = "sink-sqlite")]
+            Self::Sqlite(_) => "sqlite",
+        }
+    }
+
     pub async fn build(self) -> Result<Sink, BuildError> {
         Ok(match self {
             Self::BlackHole(inner) => Sink::BlackHole(inner.build()?),
@@ -120,9 +138,16 @@ impl Sink {
         self,
         name: &ComponentName,
         receiver: Receiver,
-    ) -> Result<tokio::task::JoinHandle<()>, StartingError> {
+    ) -> Result<tokio::task::JoinHandle<Result<(), ExecutionError>>, StartingError> {
+        let metrics = crate::components::metrics::register(
+            name,
+            COMPONENT_KIND,
+            self.flavor(),
+            Some(receiver.probe()),
+        );
+        let receiver = receiver.with_metrics(metrics);
         let span = tracing::info_span!(
-            "component",
+            COMPONENT_SPAN,
             name = name.as_ref(),
             kind = COMPONENT_KIND,
             flavor = self.flavor(),
@@ -151,7 +176,10 @@ trait Preparable {
 }
 
 trait Executable {
-    fn execute(self, receiver: Receiver) -> impl std::future::Future<Output = ()> + Send;
+    fn execute(
+        self,
+        receiver: Receiver,
+    ) -> impl std::future::Future<Output = Result<(), ExecutionError>> + Send;
 }
 
 async fn run<
@@ -162,7 +190,7 @@ async fn run<
     element: P,
     span: tracing::Span,
     receiver: Receiver,
-) -> Result<tokio::task::JoinHandle<()>, StartingError> {
+) -> Result<tokio::task::JoinHandle<Result<(), ExecutionError>>, StartingError> {
     use tracing::Instrument;
 
     let prepared = element.prepare().await.map_err(|err| err.into())?;
@@ -3,15 +3,29 @@ use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
 use metrics::{Label, Recorder};
 use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusRecorder};
 
-use crate::event::metric::{EventMetric, EventMetricHeader, EventMetricValue};
+use crate::components::ExecutionError;
+use crate::event::metric::{
+    EventMetric, EventMetricHeader, EventMetricKind, EventMetricValue, HistogramBucket,
+};
+use crate::event::naming::MetricNaming;
 use crate::event::Event;
 use crate::prelude::Receiver;
 
-#[derive(Clone, Debug, Default, serde::Deserialize)]
+/// Most times a sample of a distribution gets recorded, the recorder only accepting
+/// samples one by one.
+const MAX_SAMPLE_RATE: u32 = 1000;
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct Config {
     address: Option<String>,
     bucket_duration: Option<u64>,
+    /// Upper limits of the buckets of the distributions, rendered as summaries when not set.
+    /// The histograms keep the buckets they're received with.
+    buckets: Option<Vec<f64>>,
+    /// Overrides the Prometheus naming conventions
+    #[serde(default)]
+    naming: crate::event::naming::Config,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -20,6 +34,8 @@ pub enum BuildError {
     InvalidAddress(#[source] std::net::AddrParseError),
     #[error("unable to build bucket")]
     InvalidBucket(#[source] metrics_exporter_prometheus::BuildError),
+    #[error("unable to use histogram buckets")]
+    UnableToUseBuckets(#[source] metrics_exporter_prometheus::BuildError),
 }
 
 impl Config {
@@ -36,9 +52,16 @@ impl Config {
             .with_http_listener(address)
             .set_bucket_duration(bucket_duration)
             .map_err(BuildError::InvalidBucket)?;
+        let builder = match self.buckets {
+            Some(buckets) => builder
+                .set_buckets(&buckets)
+                .map_err(BuildError::UnableToUseBuckets)?,
+            None => builder,
+        };
         Ok(Sink {
             state: Stale { builder },
             metadata: metrics::Metadata::new("", metrics::Level::TRACE, None),
+            naming: self.naming.build(MetricNaming::prometheus()),
         })
     }
 }
@@ -62,6 +85,7 @@ pub(crate) struct Running {
 pub struct Sink<S = Stale> {
     state: S,
     metadata: metrics::Metadata<'static>,
+    naming: MetricNaming,
 }
 
 impl<S> Sink<S> {
@@ -90,35 +114,131 @@ impl super::Preparable for Sink<Stale> {
         Ok(Sink {
             state: Running { recorder, exporter },
             metadata: self.metadata,
+            naming: self.naming,
         })
     }
 }
 
 impl Sink<Running> {
+    /// Registers a gauge named after the key, with a suffix and an extra label.
+    fn derived_gauge(
+        &self,
+        key: &metrics::Key,
+        suffix: Option<&str>,
+        label: Option<Label>,
+    ) -> metrics::Gauge {
+        let name = match suffix {
+            Some(suffix) => format!("{}_{suffix}", key.name()),
+            None => key.name().to_string(),
+        };
+        let labels = key.labels().cloned().chain(label).collect::<Vec<_>>();
+        self.state
+            .recorder
+            .register_gauge(&metrics::Key::from_parts(name, labels), &self.metadata)
+    }
+
+    /// Exposes the buckets, count and sum of a histogram like the recorder would, their
+    /// values being added to the previous ones.
+    fn handle_histogram(
+        &self,
+        key: &metrics::Key,
+        mut buckets: Vec<HistogramBucket>,
+        count: u64,
+        sum: f64,
+    ) {
+        buckets.retain(|bucket| !bucket.upper_limit.is_nan());
+        buckets.sort_by(|a, b| a.upper_limit.total_cmp(&b.upper_limit));
+        let mut cumulative = 0;
+        for bucket in buckets {
+            cumulative += bucket.count;
+            if bucket.upper_limit.is_finite() {
+                let label = Label::new("le", bucket.upper_limit.to_string());
+                self.derived_gauge(key, Some("bucket"), Some(label))
+                    .increment(cumulative as f64);
+            }
+        }
+        // the count is the last bucket, even when not sent
+        let count = count.max(cumulative) as f64;
+        self.derived_gauge(key, Some("bucket"), Some(Label::new("le", "+Inf")))
+            .increment(count);
+        self.derived_gauge(key, Some("sum"), None).increment(sum);
+        self.derived_gauge(key, Some("count"), None)
+            .increment(count);
+    }
+
     fn handle_metric(&mut self, event_metric: EventMetric) {
-        let EventMetric {
-            timestamp: _,
-            header,
-            value,
-        } = event_metric;
+        let kind = event_metric.kind();
+        let EventMetric { header, value, .. } = event_metric;
         let EventMetricHeader { name, tags } = header;
         let labels = tags
             .into_iter()
             .map(|(key, value)| Label::new(key, value))
             .collect::<Vec<_>>();
-        let key = metrics::Key::from_parts(name.to_string(), labels);
+        let key = metrics::Key::from_parts(name.render(&self.naming, &value), labels);
         match value {
             EventMetricValue::Gauge(inner) => {
-                self.state
-                    .recorder
-                    .register_gauge(&key, &self.metadata)
-                    .set(inner);
+                let gauge = self.state.recorder.register_gauge(&key, &self.metadata);
+                match kind {
+                    EventMetricKind::Absolute => gauge.set(inner),
+                    EventMetricKind::Incremental => gauge.increment(inner),
+                }
             }
             EventMetricValue::Counter(inner) => {
+                let counter = self.state.recorder.register_counter(&key, &self.metadata);
+                match kind {
+                    EventMetricKind::Absolute => counter.absolute(inner),
+                    EventMetricKind::Incremental => counter.increment(inner),
+                }
+            }
+            // the samples of a total would be recorded again with every event
+            EventMetricValue::Histogram { .. } | EventMetricValue::Distribution { .. }
+                if kind == EventMetricKind::Absolute =>
+            {
+                tracing::debug!(
+                    "absolute {} discarded, it should be converted to incremental",
+                    key.name()
+                );
+            }
+            // the recorder only accepts samples, replaying the buckets would lose the sum
+            EventMetricValue::Histogram {
+                buckets,
+                count,
+                sum,
+            } => self.handle_histogram(&key, buckets, count, sum),
+            EventMetricValue::Distribution { samples } => {
+                let histogram = self.state.recorder.register_histogram(&key, &self.metadata);
+                for sample in samples {
+                    if sample.rate > MAX_SAMPLE_RATE {
+                        tracing::debug!(
+                            "sample rate of {} capped to {MAX_SAMPLE_RATE}",
+                            key.name()
+                        );
+                    }
+                    for _ in 0..sample.rate.min(MAX_SAMPLE_RATE) {
+                        histogram.record(sample.value);
+                    }
+                }
+            }
+            // quantiles can't be merged, so they are exposed as they are
+            EventMetricValue::Summary {
+                quantiles,
+                count,
+                sum,
+            } => {
+                for quantile in quantiles {
+                    let label = Label::new("quantile", quantile.quantile.to_string());
+                    self.derived_gauge(&key, None, Some(label))
+                        .set(quantile.value);
+                }
+                for (suffix, value) in [("sum", sum), ("count", count as f64)] {
+                    self.derived_gauge(&key, Some(suffix), None).set(value);
+                }
+            }
+            EventMetricValue::Set { values } => {
                 self.state
                     .recorder
-                    .register_counter(&key, &self.metadata)
-                    .increment(inner);
+                    .register_gauge(&key, &self.metadata)
+                    .set(values.len() as f64);
             }
         }
     }
@@ -132,11 +252,137 @@ impl Sink<Running> {
 }
 
 impl super::Executable for Sink<Running> {
-    async fn execute(mut self, mut receiver: Receiver) {
+    async fn execute(mut self, mut receiver: Receiver) -> Result<(), ExecutionError> {
         tracing::info!("starting");
         while let Some(input) = receiver.recv().await {
             self.handle(input);
         }
         tracing::info!("stopping");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use metrics_exporter_prometheus::PrometheusBuilder;
+
+    use super::{Running, Sink};
+    use crate::event::metric::{
+        DistributionSample, EventMetric, EventMetricKind, EventMetricValue, HistogramBucket,
+    };
+
+    #[tokio::test]
+    async fn should_render_metrics() {
+        let recorder = PrometheusBuilder::new()
+            .set_buckets(&[0.1, 1.0])
+            .unwrap()
+            .build_recorder();
+        let handle = recorder.handle();
+        let mut sink = Sink {
+            state: Running {
+                recorder,
+                exporter: tokio::spawn(async {}),
+            },
+            metadata: metrics::Metadata::new("", metrics::Level::TRACE, None),
+            naming: super::MetricNaming::prometheus(),
+        };
+
+        let value = EventMetricValue::Histogram {
+            buckets: vec![
+                HistogramBucket {
+                    upper_limit: 0.1,
+                    count: 2,
+                },
+                HistogramBucket {
+                    upper_limit: 1.0,
+                    count: 1,
+                },
+            ],
+            count: 3,
+            sum: 1.1,
+        };
+        sink.handle_metric(EventMetric::new(0, "http", "latency", value));
+        let value = EventMetricValue::Distribution {
+            samples: vec![DistributionSample {
+                value: 0.5,
+                rate: 4,
+            }],
+        };
+        sink.handle_metric(EventMetric::new(0, "", "duration", value));
+
+        let value = EventMetricValue::Counter(10);
+        let absolute =
+            EventMetric::new(0, "", "requests", value).with_kind(EventMetricKind::Absolute);
+        sink.handle_metric(absolute.clone());
+        sink.handle_metric(absolute);
+
+        let rendered = handle.render();
+        assert!(rendered.contains("requests_total 10\n"), "{rendered}");
+        assert!(
+            rendered.contains("http_latency_bucket{le=\"0.1\"} 2\n"),
+            "{rendered}"
+        );
+        assert!(
+            rendered.contains("http_latency_bucket{le=\"1\"} 3\n"),
+            "{rendered}"
+        );
+        assert!(rendered.contains("http_latency_count 3\n"), "{rendered}");
+        assert!(rendered.contains("http_latency_sum 1.1\n"), "{rendered}");
+        assert!(
+            rendered.contains("duration_bucket{le=\"1\"} 4\n"),
+            "{rendered}"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_not_replay_large_histograms() {
+        let recorder = PrometheusBuilder::new()
+            .set_buckets(&[1.0])
+            .unwrap()
+            .build_recorder();
+        let handle = recorder.handle();
+        let mut sink = Sink {
+            state: Running {
+                recorder,
+                exporter: tokio::spawn(async {}),
+            },
+            metadata: metrics::Metadata::new("", metrics::Level::TRACE, None),
+            naming: super::MetricNaming::prometheus(),
+        };
+
+        let value = EventMetricValue::Histogram {
+            buckets: vec![
+                HistogramBucket {
+                    upper_limit: f64::INFINITY,
+                    count: 1_000_000_000,
+                },
+                HistogramBucket {
+                    upper_limit: 0.5,
+                    count: 2,
+                },
+            ],
+            count: 1_000_000_002,
+            sum: 42.0,
+        };
+        sink.handle_metric(EventMetric::new(0, "", "latency", value));
+        let value = EventMetricValue::Distribution {
+            samples: vec![DistributionSample {
+                value: 0.5,
+                rate: u32::MAX,
+            }],
+        };
+        sink.handle_metric(EventMetric::new(0, "", "duration", value));
+
+        let rendered = handle.render();
+        assert!(
+            rendered.contains("latency_bucket{le=\"0.5\"} 2\n"),
+            "{rendered}"
+        );
+        assert!(
+            rendered.contains("latency_bucket{le=\"+Inf\"} 1000000002\n"),
+            "{rendered}"
+        );
+        assert!(rendered.contains("latency_sum 42\n"), "{rendered}");
+        assert!(rendered.contains("duration_count 1000\n"), "{rendered}");
     }
 }
@@ -1,13 +1,14 @@
 use sqlx::types::Json;
 use sqlx::SqliteConnection;
 
+use crate::components::ExecutionError;
 use crate::event::log::EventLog;
 use crate::event::metric::EventMetric;
+use crate::event::timestamp::now;
 use crate::event::Event;
-use crate::helper::now;
 use crate::prelude::Receiver;
 
-#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct Config {
     url: Option<String>,
@@ -56,8 +57,9 @@ async fn persist_event_log(
     connection: &mut SqliteConnection,
     event: EventLog,
 ) -> Result<(), sqlx::Error> {
+    let timestamp = event.timestamp_or_received().unwrap_or_else(now);
     sqlx::query("insert into event_logs (timestamp, attributes, message) values (?,?,?)")
-        .bind(now() as i64)
+        .bind(timestamp.timestamp())
         .bind(Json(event.attributes))
         .bind(&event.message)
         .execute(&mut *connection)
@@ -72,7 +74,7 @@ async fn persist_event_metric(
     sqlx::query(
         "insert into event_metrics (timestamp, namespace, name, tags, value) values (?,?,?,?,?)",
     )
-    .bind(now() as i64)
+    .bind(event.timestamp as i64)
     .bind(event.header.name.namespace)
     .bind(event.header.name.name)
     .bind(Json(event.header.tags))
@@ -131,7 +133,7 @@ impl super::Preparable for Sink<Stale> {
 }
 
 impl super::Executable for Sink<Running> {
-    async fn execute(mut self, mut receiver: Receiver) {
+    async fn execute(mut self, mut receiver: Receiver) -> Result<(), ExecutionError> {
         tracing::info!("starting");
         while let Some(input) = receiver.recv().await {
             if let Err(err) = persist_event(&mut self.state.connection, input).await {
@@ -139,5 +141,46 @@ impl super::Executable for Sink<Running> {
             }
         }
         tracing::info!("stopping");
+        if let Err(err) = sqlx::Connection::close(self.state.connection).await {
+            tracing::error!("unable to close connection: {err:?}");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::metric::{EventMetric, EventMetricValue, SummaryQuantile};
+    use crate::sinks::Preparable;
+
+    #[tokio::test]
+    async fn should_persist_aggregated_metrics_as_json() {
+        let mut sink = super::Config::default()
+            .build()
+            .unwrap()
+            .prepare()
+            .await
+            .unwrap();
+        let value = EventMetricValue::Summary {
+            quantiles: vec![SummaryQuantile {
+                quantile: 0.99,
+                value: 12.5,
+            }],
+            count: 10,
+            sum: 42.0,
+        };
+        let event = EventMetric::new(0, "http", "latency", value);
+        super::persist_event(&mut sink.state.connection, event.into())
+            .await
+            .unwrap();
+
+        let (kind, quantile): (String, f64) = sqlx::query_as(
+            "select json_extract(value, '$.type'), json_extract(value, '$.value.quantiles[0].value') from event_metrics",
+        )
+        .fetch_one(&mut sink.state.connection)
+        .await
+        .unwrap();
+        assert_eq!(kind, "summary");
+        assert_eq!(quantile, 12.5);
     }
 }
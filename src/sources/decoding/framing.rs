@@ -0,0 +1,175 @@
+//! Framing of the streams received by the network sources.
+
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader};
+use tokio_util::sync::CancellationToken;
+
+use super::Decoding;
+use crate::components::collector::Collector;
+
+/// Longest frame accepted with an explicit length, to not allocate whatever a client asks.
+const MAX_FRAME_LENGTH: usize = 1024 * 1024;
+
+/// How the frames are delimited in a stream.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Framing {
+    /// Frames ending with `\n` or `\r\n`
+    #[default]
+    Newline,
+    /// Frames preceded by their length, as a 4 bytes big endian integer
+    LengthPrefixed,
+    /// Frames preceded by their length in ASCII and a space, as defined by RFC 6587, falling
+    /// back to newlines for the frames not starting with a digit
+    OctetCounting,
+}
+
+fn too_long(length: usize) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("frame of {length} bytes is too long"),
+    )
+}
+
+impl Framing {
+    /// Reads the next frame in the buffer, returns false at the end of the stream.
+    pub async fn read_frame<R: AsyncBufRead + Unpin>(
+        &self,
+        reader: &mut R,
+        buffer: &mut Vec<u8>,
+    ) -> std::io::Result<bool> {
+        buffer.clear();
+        match self {
+            Self::Newline => read_line(reader, buffer).await,
+            Self::LengthPrefixed => {
+                let length = match reader.read_u32().await {
+                    Ok(length) => length as usize,
+                    Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                        return Ok(false)
+                    }
+                    Err(err) => return Err(err),
+                };
+                read_exact(reader, buffer, length).await
+            }
+            Self::OctetCounting => {
+                let starts_with_digit = reader.fill_buf().await?.first().map(u8::is_ascii_digit);
+                match starts_with_digit {
+                    None => Ok(false),
+                    Some(false) => read_line(reader, buffer).await,
+                    Some(true) => {
+                        if reader.read_until(b' ', buffer).await? == 0 {
+                            return Ok(false);
+                        }
+                        let length = std::str::from_utf8(buffer)
+                            .ok()
+                            .and_then(|value| value.trim_end().parse::<usize>().ok())
+                            .ok_or_else(|| {
+                                std::io::Error::new(
+                                    std::io::ErrorKind::InvalidData,
+                                    "invalid octet count",
+                                )
+                            })?;
+                        buffer.clear();
+                        read_exact(reader, buffer, length).await
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn read_line<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    buffer: &mut Vec<u8>,
+) -> std::io::Result<bool> {
+    if reader.read_until(b'\n', buffer).await? == 0 {
+        return Ok(false);
+    }
+    if buffer.ends_with(b"\n") {
+        buffer.pop();
+    }
+    if buffer.ends_with(b"\r") {
+        buffer.pop();
+    }
+    Ok(true)
+}
+
+async fn read_exact<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    buffer: &mut Vec<u8>,
+    length: usize,
+) -> std::io::Result<bool> {
+    if length > MAX_FRAME_LENGTH {
+        return Err(too_long(length));
+    }
+    buffer.resize(length, 0);
+    reader.read_exact(buffer).await?;
+    Ok(true)
+}
+
+/// Sends the events of a stream, like a TCP connection, until it gets closed.
+pub async fn read_stream<R: AsyncRead + Unpin>(
+    stream: R,
+    decoding: Decoding,
+    framing: Framing,
+    collector: Collector,
+    shutdown: CancellationToken,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut buffer = Vec::with_capacity(4096);
+    loop {
+        let read = tokio::select! {
+            res = framing.read_frame(&mut reader, &mut buffer) => res,
+            _ = shutdown.cancelled() => break,
+        };
+        match read {
+            Ok(false) => break,
+            Ok(true) if buffer.is_empty() => {}
+            Ok(true) => match decoding.decode(&buffer) {
+                Ok(message) => {
+                    if let Err(err) = collector.send_default(message).await {
+                        tracing::error!("unable to send message: {err:?}");
+                    }
+                }
+                Err(err) => {
+                    tracing::error!("invalid message received: {err:?}")
+                }
+            },
+            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                continue;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::Framing;
+
+    #[test_case(Framing::Newline, b"first\r\nsecond\n\nthird", &["first", "second", "", "third"]; "newline")]
+    #[test_case(Framing::LengthPrefixed, b"\0\0\0\x05first\0\0\0\x06second", &["first", "second"]; "length prefixed")]
+    #[test_case(Framing::OctetCounting, b"5 first11 second\nlinethird\n", &["first", "second\nline", "third"]; "octet counting")]
+    #[tokio::test]
+    async fn should_read_frames(framing: Framing, input: &[u8], expected: &[&str]) {
+        let mut reader = tokio::io::BufReader::new(input);
+        let mut buffer = Vec::new();
+        let mut frames = Vec::new();
+        while framing.read_frame(&mut reader, &mut buffer).await.unwrap() {
+            frames.push(String::from_utf8(buffer.clone()).unwrap());
+        }
+        assert_eq!(frames, expected);
+    }
+
+    #[tokio::test]
+    async fn should_reject_long_frames() {
+        let mut reader = tokio::io::BufReader::new(&b"\xff\0\0\0hello"[..]);
+        let mut buffer = Vec::new();
+        assert!(Framing::LengthPrefixed
+            .read_frame(&mut reader, &mut buffer)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,157 @@
+//! Decoding and framing of the bytes received by the network sources.
+
+use crate::event::log::EventLog;
+use crate::event::Event;
+
+#[cfg(any(feature = "source-syslog", feature = "source-tcp-server"))]
+mod framing;
+pub mod statsd;
+pub mod syslog;
+
+#[cfg(any(feature = "source-syslog", feature = "source-tcp-server"))]
+pub use self::framing::{read_stream, Framing};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DecodingError {
+    #[error("invalid json")]
+    InvalidJson(
+        #[from]
+        #[source]
+        serde_json::Error,
+    ),
+    #[error("expected a json object")]
+    NotAnObject,
+    #[error("invalid syslog message")]
+    InvalidSyslog(
+        #[from]
+        #[source]
+        syslog::ParseError,
+    ),
+    #[error("invalid statsd line")]
+    InvalidStatsd(
+        #[from]
+        #[source]
+        statsd::ParseError,
+    ),
+}
+
+/// How each frame gets turned into an event.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Decoding {
+    /// Events serialized by another tiny-vector
+    #[default]
+    NativeJson,
+    /// Any json object, the `message` and `timestamp` keys being used when present, an
+    /// unknown timestamp format being kept as attribute
+    Json,
+    /// The whole frame as message
+    Text,
+    /// RFC 5424 or RFC 3164 messages
+    Syslog,
+    /// StatsD lines, with the DogStatsD tags, as metrics
+    Statsd,
+}
+
+impl Decoding {
+    pub fn decode(&self, frame: &[u8]) -> Result<Event, DecodingError> {
+        match self {
+            Self::NativeJson => Ok(serde_json::from_slice(frame)?),
+            Self::Json => {
+                let mut object = match serde_json::from_slice(frame)? {
+                    serde_json::Value::Object(inner) => inner,
+                    _ => return Err(DecodingError::NotAnObject),
+                };
+                if !object.contains_key("message") {
+                    object.insert("message".into(), "".into());
+                }
+                let event: EventLog = serde_json::from_value(object.into())?;
+                Ok(event.into())
+            }
+            Self::Text => Ok(EventLog::new(String::from_utf8_lossy(frame)).into()),
+            Self::Syslog => Ok(syslog::parse(&String::from_utf8_lossy(frame))?.into()),
+            Self::Statsd => Ok(statsd::parse(&String::from_utf8_lossy(frame))?.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Decoding;
+
+    #[test]
+    fn should_decode_json_objects() {
+        let event = Decoding::Json
+            .decode(br#"{"level": "info", "http": {"status": 200, "error": null}, "timestamp": "2024-10-17T05:56:51Z"}"#)
+            .unwrap()
+            .into_event_log()
+            .unwrap();
+        assert_eq!(event.message, "");
+        assert_eq!(event.attributes["level"].as_text(), Some("info"));
+        assert!(event.timestamp.is_some());
+        assert_eq!(
+            serde_json::to_value(&event.attributes["http"]).unwrap(),
+            serde_json::json!({"status": 200, "error": null})
+        );
+
+        assert!(Decoding::Json.decode(b"[1, 2]").is_err());
+        assert!(Decoding::NativeJson
+            .decode(br#"{"level": "info"}"#)
+            .is_err());
+    }
+
+    #[test]
+    fn should_keep_unknown_timestamps() {
+        let event = Decoding::Json
+            .decode(br#"{"message": "hello", "timestamp": "yesterday"}"#)
+            .unwrap()
+            .into_event_log()
+            .unwrap();
+        assert_eq!(event.timestamp, None);
+        assert_eq!(event.attributes["timestamp"].as_text(), Some("yesterday"));
+
+        let event = Decoding::Json
+            .decode(br#"{"message": "hello", "timestamp": 1729144611817}"#)
+            .unwrap()
+            .into_event_log()
+            .unwrap();
+        assert_eq!(
+            event.timestamp.map(|value| value.timestamp_millis()),
+            Some(1729144611817)
+        );
+        assert!(event.attributes.is_empty());
+    }
+
+    #[test]
+    fn should_decode_native_json_of_decoded_json() {
+        for frame in [
+            br#"{"message": "hello", "timestamp": "yesterday"}"#.as_slice(),
+            br#"{"message": "hello", "timestamp": "2024-10-17T05:56:51Z"}"#,
+            br#"{"message": "hello", "@timestamp": 1729144611, "timestamp": "17/Oct/2024"}"#,
+        ] {
+            let event = Decoding::Json.decode(frame).unwrap();
+            let encoded = serde_json::to_vec(&event).unwrap();
+            assert_eq!(Decoding::NativeJson.decode(&encoded).unwrap(), event);
+        }
+
+        // written by a previous version, before the timestamp got its own field
+        let event = Decoding::NativeJson
+            .decode(br#"{"type":"log","content":{"message":"x","timestamp":"17/Oct/2024"}}"#)
+            .unwrap()
+            .into_event_log()
+            .unwrap();
+        assert_eq!(event.timestamp, None);
+        assert_eq!(event.attributes["timestamp"].as_text(), Some("17/Oct/2024"));
+    }
+
+    #[test]
+    fn should_decode_text() {
+        let event = Decoding::Text
+            .decode(b"hello world")
+            .unwrap()
+            .into_event_log()
+            .unwrap();
+        assert_eq!(event.message, "hello world");
+        assert!(event.attributes.is_empty());
+    }
+}
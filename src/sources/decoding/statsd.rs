@@ -0,0 +1,137 @@
+use std::collections::BTreeSet;
+
+use crate::event::metric::{DistributionSample, EventMetric, EventMetricKind, EventMetricValue};
+
+/// Lowest sample rate accepted, a sample standing for at most a thousand others.
+const MIN_SAMPLE_RATE: f64 = 0.001;
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ParseError {
+    #[error("missing value")]
+    MissingValue,
+    #[error("missing type")]
+    MissingType,
+    #[error("unknown type {0:?}")]
+    UnknownType(String),
+    #[error("invalid value")]
+    InvalidValue,
+    #[error("invalid sample rate")]
+    InvalidSampleRate,
+}
+
+fn parse_value(value: &str) -> Result<f64, ParseError> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or(ParseError::InvalidValue)
+}
+
+/// Parses a line like `page.views:1|c|@0.5|#env:prod,canary`, the DogStatsD tags without
+/// value being set to `true`.
+pub fn parse(line: &str) -> Result<EventMetric, ParseError> {
+    let (name, rest) = line
+        .trim()
+        .split_once(':')
+        .ok_or(ParseError::MissingValue)?;
+    let mut parts = rest.split('|');
+    let value = parts.next().unwrap_or_default();
+    let kind = parts.next().ok_or(ParseError::MissingType)?;
+
+    let mut rate = 1.0;
+    let mut tags = Vec::new();
+    for part in parts {
+        if let Some(sample_rate) = part.strip_prefix('@') {
+            rate = sample_rate
+                .parse::<f64>()
+                .ok()
+                .filter(|r| (MIN_SAMPLE_RATE..=1.0).contains(r))
+                .ok_or(ParseError::InvalidSampleRate)?;
+        } else if let Some(list) = part.strip_prefix('#') {
+            tags.extend(
+                list.split(',')
+                    .filter(|tag| !tag.is_empty())
+                    .map(|tag| tag.split_once(':').unwrap_or((tag, "true"))),
+            );
+        }
+        // other extensions, like the container id, are ignored
+    }
+
+    let mut incremental = false;
+    let value = match kind {
+        "c" => {
+            let count = (parse_value(value)? / rate).round();
+            if count < 0.0 {
+                return Err(ParseError::InvalidValue);
+            }
+            EventMetricValue::Counter(count as u64)
+        }
+        "g" => {
+            // a sign makes the gauge change relatively to its current value
+            incremental = value.starts_with(['+', '-']);
+            EventMetricValue::Gauge(parse_value(value)?)
+        }
+        "ms" | "h" | "d" => EventMetricValue::Distribution {
+            samples: vec![DistributionSample {
+                value: parse_value(value)?,
+                rate: (1.0 / rate).round() as u32,
+            }],
+        },
+        "s" => EventMetricValue::Set {
+            values: BTreeSet::from([value.to_string()]),
+        },
+        other => return Err(ParseError::UnknownType(other.to_string())),
+    };
+
+    let mut metric = EventMetric::new(crate::helper::now(), "", name.to_string(), value);
+    if incremental {
+        metric = metric.with_kind(EventMetricKind::Incremental);
+    }
+    for (name, value) in tags {
+        metric.add_tag(name.to_string(), value.to_string());
+    }
+    Ok(metric)
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::{parse, ParseError};
+    use crate::event::metric::{DistributionSample, EventMetricKind, EventMetricValue};
+
+    #[test_case("page.views:1|c", EventMetricValue::Counter(1); "counter")]
+    #[test_case("page.views:1|c|@0.1", EventMetricValue::Counter(10); "sampled counter")]
+    #[test_case("fuel.level:0.5|g", EventMetricValue::Gauge(0.5); "gauge")]
+    #[test_case("request.time:320|ms|@0.5", EventMetricValue::Distribution { samples: vec![DistributionSample { value: 320.0, rate: 2 }] }; "timing")]
+    #[test_case("request.time:320|ms|@0.001", EventMetricValue::Distribution { samples: vec![DistributionSample { value: 320.0, rate: 1000 }] }; "lowest rate")]
+    #[test_case("users.unique:alice|s", EventMetricValue::Set { values: ["alice".to_string()].into() }; "set")]
+    fn should_parse_values(line: &str, expected: EventMetricValue) {
+        let metric = parse(line).unwrap();
+        assert_eq!(metric.value, expected);
+        assert!(metric.header.name.namespace.is_empty());
+    }
+
+    #[test]
+    fn should_parse_dogstatsd_tags() {
+        let metric = parse("queue.size:-3|g|#env:prod,canary,region:eu-west-1\n").unwrap();
+        assert_eq!(metric.header.name.name, "queue.size");
+        assert_eq!(metric.value, EventMetricValue::Gauge(-3.0));
+        assert_eq!(metric.kind(), EventMetricKind::Incremental);
+        assert_eq!(metric.header.tags.len(), 3);
+        assert_eq!(metric.header.tags["env"], "prod");
+        assert_eq!(metric.header.tags["canary"], "true");
+        assert_eq!(metric.header.tags["region"], "eu-west-1");
+    }
+
+    #[test_case("page.views", ParseError::MissingValue; "no value")]
+    #[test_case("page.views:1", ParseError::MissingType; "no type")]
+    #[test_case("page.views:1|x", ParseError::UnknownType("x".into()); "unknown type")]
+    #[test_case("page.views:-1|c", ParseError::InvalidValue; "negative counter")]
+    #[test_case("page.views:1|c|@2", ParseError::InvalidSampleRate; "invalid rate")]
+    #[test_case("request.time:320|ms|@1e-10", ParseError::InvalidSampleRate; "too low rate")]
+    #[test_case("request.time:320|ms|@0", ParseError::InvalidSampleRate; "zero rate")]
+    fn should_reject_invalid_lines(line: &str, expected: ParseError) {
+        assert_eq!(parse(line).unwrap_err(), expected);
+    }
+}
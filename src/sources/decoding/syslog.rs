@@ -0,0 +1,341 @@
+use chrono::{Datelike, NaiveDateTime};
+use indexmap::IndexMap;
+
+use crate::event::log::{EventLog, EventLogAttribute};
+use crate::event::timestamp::{self, Timestamp};
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ParseError {
+    #[error("missing priority")]
+    MissingPriority,
+    #[error("invalid priority")]
+    InvalidPriority,
+    #[error("invalid timestamp")]
+    InvalidTimestamp,
+    #[error("invalid structured data")]
+    InvalidStructuredData,
+}
+
+const FACILITIES: [&str; 24] = [
+    "kern",
+    "user",
+    "mail",
+    "daemon",
+    "auth",
+    "syslog",
+    "lpr",
+    "news",
+    "uucp",
+    "cron",
+    "authpriv",
+    "ftp",
+    "ntp",
+    "security",
+    "console",
+    "solaris-cron",
+    "local0",
+    "local1",
+    "local2",
+    "local3",
+    "local4",
+    "local5",
+    "local6",
+    "local7",
+];
+
+const SEVERITIES: [&str; 8] = [
+    "emerg", "alert", "crit", "err", "warning", "notice", "info", "debug",
+];
+
+/// Splits the first word, the rest being empty when there is no space.
+fn next_word(value: &str) -> (&str, &str) {
+    value.split_once(' ').unwrap_or((value, ""))
+}
+
+/// Returns nothing for the `-` nil value of RFC 5424.
+fn not_nil(value: &str) -> Option<&str> {
+    (value != "-").then_some(value)
+}
+
+fn parse_priority(value: &str) -> Result<(u8, &str), ParseError> {
+    let rest = value.strip_prefix('<').ok_or(ParseError::MissingPriority)?;
+    let (priority, rest) = rest.split_once('>').ok_or(ParseError::MissingPriority)?;
+    let priority = priority
+        .parse::<u8>()
+        .ok()
+        .filter(|p| *p < 192)
+        .ok_or(ParseError::InvalidPriority)?;
+    Ok((priority, rest))
+}
+
+/// Parameters of each element of the structured data, by element id.
+type StructuredData = IndexMap<String, IndexMap<String, String>>;
+
+/// Parses the RFC 5424 structured data, like `[origin ip="10.0.0.1"][meta seq="1"]`.
+fn parse_structured_data(value: &str) -> Result<(StructuredData, &str), ParseError> {
+    let mut result = IndexMap::new();
+    if let Some(rest) = value.strip_prefix('-') {
+        return Ok((result, rest));
+    }
+    let mut rest = value;
+    while let Some(element) = rest.strip_prefix('[') {
+        let end = element
+            .find([' ', ']'])
+            .ok_or(ParseError::InvalidStructuredData)?;
+        let id = element[..end].to_string();
+        let mut params = IndexMap::new();
+        let mut cursor = &element[end..];
+        loop {
+            cursor = cursor.trim_start_matches(' ');
+            if let Some(after) = cursor.strip_prefix(']') {
+                cursor = after;
+                break;
+            }
+            let (name, after) = cursor
+                .split_once("=\"")
+                .ok_or(ParseError::InvalidStructuredData)?;
+            let mut value = String::new();
+            let mut chars = after.char_indices();
+            let consumed = loop {
+                match chars.next() {
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, escaped @ ('"' | '\\' | ']'))) => value.push(escaped),
+                        Some((_, other)) => {
+                            value.push('\\');
+                            value.push(other);
+                        }
+                        None => return Err(ParseError::InvalidStructuredData),
+                    },
+                    Some((index, '"')) => break index + 1,
+                    Some((_, other)) => value.push(other),
+                    None => return Err(ParseError::InvalidStructuredData),
+                }
+            };
+            params.insert(name.to_string(), value);
+            cursor = &after[consumed..];
+        }
+        result.insert(id, params);
+        rest = cursor;
+    }
+    Ok((result, rest))
+}
+
+struct Message<'a> {
+    priority: u8,
+    version: Option<u8>,
+    timestamp: Option<Timestamp>,
+    hostname: Option<&'a str>,
+    appname: Option<&'a str>,
+    procid: Option<&'a str>,
+    msgid: Option<&'a str>,
+    structured_data: StructuredData,
+    message: &'a str,
+}
+
+fn parse_rfc5424(priority: u8, version: u8, rest: &str) -> Result<Message<'_>, ParseError> {
+    let (time, rest) = next_word(rest);
+    let timestamp = match not_nil(time) {
+        Some(time) => Some(timestamp::parse(time, None).ok_or(ParseError::InvalidTimestamp)?),
+        None => None,
+    };
+    let (hostname, rest) = next_word(rest);
+    let (appname, rest) = next_word(rest);
+    let (procid, rest) = next_word(rest);
+    let (msgid, rest) = next_word(rest);
+    let (structured_data, rest) = parse_structured_data(rest)?;
+    let message = rest.strip_prefix(' ').unwrap_or(rest);
+    Ok(Message {
+        priority,
+        version: Some(version),
+        timestamp,
+        hostname: not_nil(hostname),
+        appname: not_nil(appname),
+        procid: not_nil(procid),
+        msgid: not_nil(msgid),
+        structured_data,
+        message: message.strip_prefix('\u{feff}').unwrap_or(message),
+    })
+}
+
+/// Timestamps like `Oct 11 22:14:15`, in the current year, or RFC 3339 ones.
+fn parse_rfc3164_timestamp(value: &str) -> Option<(Timestamp, &str)> {
+    let (word, rest) = next_word(value);
+    if let Some(timestamp) = timestamp::parse(word, None) {
+        return Some((timestamp, rest));
+    }
+    let text = value.get(..15)?;
+    let year = timestamp::now().year();
+    let parsed =
+        NaiveDateTime::parse_from_str(&format!("{year} {text}"), "%Y %b %e %H:%M:%S").ok()?;
+    Some((parsed.and_utc(), value[15..].trim_start_matches(' ')))
+}
+
+fn parse_rfc3164(priority: u8, rest: &str) -> Message<'_> {
+    let mut message = Message {
+        priority,
+        version: None,
+        timestamp: None,
+        hostname: None,
+        appname: None,
+        procid: None,
+        msgid: None,
+        structured_data: IndexMap::new(),
+        message: rest,
+    };
+    let Some((timestamp, rest)) = parse_rfc3164_timestamp(rest) else {
+        return message;
+    };
+    message.timestamp = Some(timestamp);
+    message.message = rest;
+    let (word, after) = next_word(rest);
+    let rest = match word.ends_with(':') || word.contains('[') {
+        true => rest,
+        false => {
+            message.hostname = Some(word);
+            after
+        }
+    };
+    message.message = rest;
+    // the tag, like `sshd[1234]:`
+    let (word, after) = next_word(rest);
+    if let Some(tag) = word.strip_suffix(':') {
+        match tag.split_once('[') {
+            Some((appname, procid)) => {
+                message.appname = Some(appname);
+                message.procid = procid.strip_suffix(']');
+            }
+            None => message.appname = Some(tag),
+        }
+        message.message = after;
+    }
+    message
+}
+
+/// Parses a RFC 5424 or RFC 3164 message, the fields being set as attributes.
+pub fn parse(line: &str) -> Result<EventLog, ParseError> {
+    let (priority, rest) = parse_priority(line.trim_end_matches(['\r', '\n']))?;
+    let (word, after) = next_word(rest);
+    let message = match word.parse::<u8>() {
+        Ok(version) if version > 0 => parse_rfc5424(priority, version, after)?,
+        _ => parse_rfc3164(priority, rest),
+    };
+
+    let mut event = EventLog::new(message.message)
+        .with_attribute("facility", FACILITIES[usize::from(message.priority >> 3)])
+        .with_attribute("severity", SEVERITIES[usize::from(message.priority & 7)]);
+    event.timestamp = message.timestamp;
+    if let Some(version) = message.version {
+        event = event.with_attribute("version", u64::from(version));
+    }
+    let fields = [
+        ("hostname", message.hostname),
+        ("appname", message.appname),
+        ("procid", message.procid),
+        ("msgid", message.msgid),
+    ];
+    for (name, value) in fields {
+        if let Some(value) = value {
+            event = event.with_attribute(name, value.to_string());
+        }
+    }
+    if !message.structured_data.is_empty() {
+        let elements = message
+            .structured_data
+            .into_iter()
+            .map(|(id, params)| {
+                let params = params
+                    .into_iter()
+                    .map(|(name, value)| (name.into(), EventLogAttribute::from(value)))
+                    .collect();
+                (id.into(), EventLogAttribute::Map(params))
+            })
+            .collect();
+        event = event.with_attribute("structured_data", EventLogAttribute::Map(elements));
+    }
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Datelike;
+
+    use super::{parse, ParseError};
+
+    #[test]
+    fn should_parse_rfc5424() {
+        let event = parse(
+            r#"<165>1 2003-10-11T22:14:15.003Z mymachine.example.com evntslog - ID47 [exampleSDID@32473 iut="3" eventSource="Application \"main\""][meta seq="1"] An application event"#,
+        )
+        .unwrap();
+        assert_eq!(event.message, "An application event");
+        assert_eq!(
+            event.timestamp,
+            Some("2003-10-11T22:14:15.003Z".parse().unwrap())
+        );
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({
+                "message": "An application event",
+                "@timestamp": "2003-10-11T22:14:15.003Z",
+                "facility": "local4",
+                "severity": "notice",
+                "version": 1,
+                "hostname": "mymachine.example.com",
+                "appname": "evntslog",
+                "msgid": "ID47",
+                "structured_data": {
+                    "exampleSDID@32473": {"iut": "3", "eventSource": "Application \"main\""},
+                    "meta": {"seq": "1"},
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn should_parse_rfc5424_with_nil_values() {
+        let event = parse("<14>1 - - - - - -").unwrap();
+        assert_eq!(event.message, "");
+        assert!(event.timestamp.is_none());
+        assert!(!event.attributes.contains_key("hostname"));
+    }
+
+    #[test]
+    fn should_parse_rfc3164() {
+        let event =
+            parse("<34>Oct  1 22:14:15 mymachine su[230]: 'su root' failed on /dev/pts/8").unwrap();
+        assert_eq!(event.message, "'su root' failed on /dev/pts/8");
+        assert_eq!(event.attributes["facility"].as_text(), Some("auth"));
+        assert_eq!(event.attributes["severity"].as_text(), Some("crit"));
+        assert_eq!(event.attributes["hostname"].as_text(), Some("mymachine"));
+        assert_eq!(event.attributes["appname"].as_text(), Some("su"));
+        assert_eq!(event.attributes["procid"].as_text(), Some("230"));
+        let timestamp = event.timestamp.unwrap();
+        assert_eq!(timestamp.year(), crate::event::timestamp::now().year());
+        assert_eq!(timestamp.to_rfc3339()[4..], *"-10-01T22:14:15+00:00");
+    }
+
+    #[test]
+    fn should_keep_unparsable_rfc3164_content() {
+        let event = parse("<13>hello world").unwrap();
+        assert_eq!(event.message, "hello world");
+        assert!(event.timestamp.is_none());
+        assert_eq!(event.attributes["severity"].as_text(), Some("notice"));
+    }
+
+    #[test]
+    fn should_reject_invalid_messages() {
+        assert_eq!(parse("hello").unwrap_err(), ParseError::MissingPriority);
+        assert_eq!(
+            parse("<200>hello").unwrap_err(),
+            ParseError::InvalidPriority
+        );
+        assert_eq!(
+            parse("<14>1 yesterday - - - - -").unwrap_err(),
+            ParseError::InvalidTimestamp
+        );
+        assert_eq!(
+            parse(r#"<14>1 - - - - - [meta seq="1] hello"#).unwrap_err(),
+            ParseError::InvalidStructuredData
+        );
+    }
+}
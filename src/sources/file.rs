@@ -0,0 +1,845 @@
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::sync::CancellationToken;
+
+use crate::components::collector::Collector;
+use crate::components::output::ComponentWithOutputs;
+use crate::components::ExecutionError;
+use crate::event::log::EventLog;
+
+/// Bytes read from a file before giving a chance to the other files.
+const MAX_READ_SIZE: usize = 1024 * 1024;
+const DEFAULT_MAX_LINE_BYTES: usize = 100 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BuildError {
+    #[error("no pattern to include")]
+    NothingIncluded,
+    #[error("invalid pattern {pattern:?}")]
+    InvalidPattern {
+        pattern: String,
+        #[source]
+        cause: glob::PatternError,
+    },
+    #[error("invalid multiline start pattern")]
+    InvalidStartPattern(
+        #[from]
+        #[source]
+        regex::Error,
+    ),
+}
+
+/// Where to start reading the files found at startup without checkpoint.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadFrom {
+    #[default]
+    Beginning,
+    End,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+pub struct MultilineConfig {
+    /// Regex matching the first line of an event, the following lines being appended to it
+    start_pattern: String,
+    /// Delay without new line after which the last event gets sent, in ms
+    timeout: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+pub struct Config {
+    /// Glob patterns of the files to read
+    include: Vec<String>,
+    /// Glob patterns of the files to ignore, like the rotated files
+    #[serde(default)]
+    exclude: Vec<String>,
+    /// Attribute containing the path of the file
+    path_field: Option<String>,
+    /// Where the read offsets are saved, for a restart to resume where it stopped
+    checkpoint_file: Option<PathBuf>,
+    #[serde(default)]
+    read_from: ReadFrom,
+    /// Interval between looking for new files and lines, in ms
+    interval: Option<u64>,
+    /// Longest line, in bytes, the longer lines being skipped
+    max_line_bytes: Option<usize>,
+    multiline: Option<MultilineConfig>,
+}
+
+impl ComponentWithOutputs for Config {}
+
+fn compile_patterns(patterns: &[String]) -> Result<Vec<glob::Pattern>, BuildError> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            glob::Pattern::new(pattern).map_err(|cause| BuildError::InvalidPattern {
+                pattern: pattern.clone(),
+                cause,
+            })
+        })
+        .collect()
+}
+
+impl Config {
+    pub fn build(self) -> Result<Source, BuildError> {
+        if self.include.is_empty() {
+            return Err(BuildError::NothingIncluded);
+        }
+        // only validating, the inclusion patterns are walked by glob
+        compile_patterns(&self.include)?;
+        let multiline = match self.multiline {
+            Some(config) => Some(Multiline {
+                start_pattern: regex::Regex::new(&config.start_pattern)?,
+                timeout: Duration::from_millis(config.timeout.unwrap_or(1000)),
+            }),
+            None => None,
+        };
+        Ok(Source {
+            state: Stale {
+                include: self.include,
+                exclude: compile_patterns(&self.exclude)?,
+                path_field: self.path_field.unwrap_or_else(|| String::from("file")),
+                checkpoint_file: self.checkpoint_file,
+                read_from: self.read_from,
+                interval: Duration::from_millis(self.interval.unwrap_or(1000)),
+                max_line_bytes: self.max_line_bytes.unwrap_or(DEFAULT_MAX_LINE_BYTES),
+                multiline,
+            },
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum StartingError {
+    #[error("unable to read checkpoints from {path:?}")]
+    UnableToReadCheckpoints {
+        path: PathBuf,
+        #[source]
+        cause: std::io::Error,
+    },
+    #[error("invalid checkpoints in {path:?}")]
+    InvalidCheckpoints {
+        path: PathBuf,
+        #[source]
+        cause: serde_json::Error,
+    },
+}
+
+struct Multiline {
+    start_pattern: regex::Regex,
+    timeout: Duration,
+}
+
+/// Identifies a file, even once renamed by a rotation.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+struct FileId {
+    device: u64,
+    inode: u64,
+}
+
+#[cfg(unix)]
+fn file_id(_path: &Path, metadata: &std::fs::Metadata) -> FileId {
+    use std::os::unix::fs::MetadataExt;
+
+    FileId {
+        device: metadata.dev(),
+        inode: metadata.ino(),
+    }
+}
+
+/// Without inodes, a renamed file is considered as a new file.
+#[cfg(not(unix))]
+fn file_id(path: &Path, _metadata: &std::fs::Metadata) -> FileId {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    path.hash(&mut hasher);
+    FileId {
+        device: 0,
+        inode: hasher.finish(),
+    }
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+struct Checkpoint {
+    #[serde(flatten)]
+    id: FileId,
+    /// Last known path, the files being identified by their id
+    path: PathBuf,
+    offset: u64,
+}
+
+impl Checkpoint {
+    /// Whether the file is still at the same path, even when not matching the patterns anymore.
+    async fn exists(&self) -> bool {
+        tokio::fs::metadata(&self.path)
+            .await
+            .is_ok_and(|metadata| file_id(&self.path, &metadata) == self.id)
+    }
+}
+
+async fn read_checkpoints(path: &Path) -> Result<HashMap<FileId, Checkpoint>, StartingError> {
+    let content = match tokio::fs::read(path).await {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(cause) => {
+            return Err(StartingError::UnableToReadCheckpoints {
+                path: path.to_path_buf(),
+                cause,
+            })
+        }
+    };
+    let checkpoints = serde_json::from_slice::<Vec<Checkpoint>>(&content).map_err(|cause| {
+        StartingError::InvalidCheckpoints {
+            path: path.to_path_buf(),
+            cause,
+        }
+    })?;
+    Ok(checkpoints
+        .into_iter()
+        .map(|checkpoint| (checkpoint.id, checkpoint))
+        .collect())
+}
+
+/// Replaces the checkpoints at once, so that a crash doesn't leave a partial file.
+async fn write_checkpoints(path: &Path, checkpoints: &[&Checkpoint]) -> std::io::Result<()> {
+    let content = serde_json::to_vec(checkpoints)?;
+    let temporary = path.with_extension("tmp");
+    tokio::fs::write(&temporary, content).await?;
+    tokio::fs::rename(&temporary, path).await
+}
+
+/// Lines of an event spanning several lines, waiting for the start of the next event.
+struct Pending {
+    text: String,
+    updated_at: Instant,
+}
+
+struct Watcher {
+    path: PathBuf,
+    file: File,
+    /// Position after the last sent line, or before the pending lines
+    offset: u64,
+    /// Position of the read cursor
+    position: u64,
+    /// Bytes read after the last complete line
+    partial: Vec<u8>,
+    /// Size of the line being skipped for being too long
+    discarded: Option<u64>,
+    pending: Option<Pending>,
+    /// Still matching the patterns at the last scan
+    found: bool,
+}
+
+struct Line {
+    text: String,
+    start: u64,
+    end: u64,
+}
+
+impl Watcher {
+    /// Opens the file at the given offset, or at its end.
+    async fn open(path: PathBuf, offset: Option<u64>) -> std::io::Result<Self> {
+        let mut file = File::open(&path).await?;
+        let size = file.metadata().await?.len();
+        let offset = match offset {
+            // the file got truncated since the checkpoint, or it's another file with the same inode
+            Some(offset) if offset > size => 0,
+            Some(offset) => offset,
+            None => size,
+        };
+        file.seek(SeekFrom::Start(offset)).await?;
+        Ok(Self {
+            path,
+            file,
+            offset,
+            position: offset,
+            partial: Vec::new(),
+            discarded: None,
+            pending: None,
+            found: true,
+        })
+    }
+
+    /// Bytes read after the last complete line, including the skipped ones.
+    fn buffered(&self) -> u64 {
+        self.partial.len() as u64 + self.discarded.unwrap_or(0)
+    }
+
+    /// Keeps the bytes of the current line, or skips them once it's too long.
+    fn append(&mut self, bytes: &[u8], max_line_bytes: usize) {
+        match self.discarded {
+            Some(ref mut size) => *size += bytes.len() as u64,
+            None if self.partial.len() + bytes.len() > max_line_bytes => {
+                self.discarded = Some((self.partial.len() + bytes.len()) as u64);
+                self.partial.clear();
+            }
+            None => self.partial.extend_from_slice(bytes),
+        }
+    }
+
+    /// Reads the lines appended since the previous call, returns true when the end of the
+    /// file has been reached.
+    async fn read_lines(
+        &mut self,
+        lines: &mut Vec<Line>,
+        max_line_bytes: usize,
+    ) -> std::io::Result<bool> {
+        let size = self.file.metadata().await?.len();
+        if size < self.position {
+            tracing::info!(path = %self.path.display(), "file truncated, reading from the start");
+            self.file.seek(SeekFrom::Start(0)).await?;
+            self.offset = 0;
+            self.position = 0;
+            self.partial.clear();
+            self.discarded = None;
+            self.pending = None;
+        }
+        let mut buffer = vec![0; 8192];
+        let mut read_size = 0;
+        while read_size < MAX_READ_SIZE {
+            let read = self.file.read(&mut buffer).await?;
+            if read == 0 {
+                return Ok(true);
+            }
+            read_size += read;
+            self.position += read as u64;
+            let mut chunk = &buffer[..read];
+            while let Some(index) = chunk.iter().position(|byte| *byte == b'\n') {
+                let start = self.position - chunk.len() as u64 - self.buffered();
+                self.append(&chunk[..index], max_line_bytes);
+                chunk = &chunk[index + 1..];
+                if let Some(size) = self.discarded.take() {
+                    tracing::warn!(
+                        path = %self.path.display(),
+                        "line of {size} bytes skipped, longer than {max_line_bytes} bytes"
+                    );
+                    continue;
+                }
+                let text = self.partial.strip_suffix(b"\r").unwrap_or(&self.partial);
+                lines.push(Line {
+                    text: String::from_utf8_lossy(text).into_owned(),
+                    start,
+                    end: self.position - chunk.len() as u64,
+                });
+                self.partial.clear();
+            }
+            self.append(chunk, max_line_bytes);
+        }
+        Ok(false)
+    }
+
+    /// Appends the line to the pending event, returns the previous event and the offset
+    /// after it when the line starts a new one.
+    fn aggregate(&mut self, line: Line, multiline: &Multiline) -> Option<(String, u64)> {
+        match self.pending {
+            Some(ref mut pending) if !multiline.start_pattern.is_match(&line.text) => {
+                pending.text.push('\n');
+                pending.text.push_str(&line.text);
+                pending.updated_at = Instant::now();
+                None
+            }
+            _ => {
+                let previous = self.pending.take();
+                self.pending = Some(Pending {
+                    text: line.text,
+                    updated_at: Instant::now(),
+                });
+                previous.map(|pending| (pending.text, line.start))
+            }
+        }
+    }
+
+    /// Position of the last complete line.
+    fn consumed(&self) -> u64 {
+        self.position - self.buffered()
+    }
+}
+
+pub(crate) struct Stale {
+    include: Vec<String>,
+    exclude: Vec<glob::Pattern>,
+    path_field: String,
+    checkpoint_file: Option<PathBuf>,
+    read_from: ReadFrom,
+    interval: Duration,
+    max_line_bytes: usize,
+    multiline: Option<Multiline>,
+}
+
+pub(crate) struct Running {
+    config: Stale,
+    /// Kept for the files not watched anymore until they're gone from the disk, in case
+    /// they match the patterns again
+    checkpoints: HashMap<FileId, Checkpoint>,
+    watchers: HashMap<FileId, Watcher>,
+    /// The files found by the first scan are read according to `read_from`
+    first_scan: bool,
+}
+
+pub struct Source<S = Stale> {
+    state: S,
+}
+
+impl<S> Source<S> {
+    pub const fn flavor(&self) -> &'static str {
+        "file"
+    }
+}
+
+impl super::Preparable for Source<Stale> {
+    type Output = Source<Running>;
+    type Error = StartingError;
+
+    async fn prepare(self) -> Result<Source<Running>, StartingError> {
+        let checkpoints = match self.state.checkpoint_file {
+            Some(ref path) => read_checkpoints(path).await?,
+            None => HashMap::new(),
+        };
+        Ok(Source {
+            state: Running {
+                config: self.state,
+                checkpoints,
+                watchers: HashMap::new(),
+                first_scan: true,
+            },
+        })
+    }
+}
+
+struct Scan {
+    files: HashMap<FileId, PathBuf>,
+    /// The directories that couldn't be read, the files inside them not being listed
+    errors: Vec<glob::GlobError>,
+}
+
+/// Finds the files matching the patterns, the glob walk being blocking.
+async fn scan(
+    include: Vec<String>,
+    exclude: Vec<glob::Pattern>,
+) -> Result<Scan, tokio::task::JoinError> {
+    tokio::task::spawn_blocking(move || {
+        let mut errors = Vec::new();
+        let files = include
+            .iter()
+            // validated when building the source
+            .filter_map(|pattern| glob::glob(pattern).ok())
+            .flatten()
+            .filter_map(|entry| entry.map_err(|err| errors.push(err)).ok())
+            .filter(|path| !exclude.iter().any(|pattern| pattern.matches_path(path)))
+            .filter_map(|path| {
+                let metadata = std::fs::metadata(&path).ok()?;
+                metadata
+                    .is_file()
+                    .then(|| (file_id(&path, &metadata), path))
+            })
+            .collect();
+        Scan { files, errors }
+    })
+    .await
+}
+
+impl Source<Running> {
+    async fn discover(&mut self) {
+        let scan = match scan(
+            self.state.config.include.clone(),
+            self.state.config.exclude.clone(),
+        )
+        .await
+        {
+            Ok(scan) => scan,
+            Err(err) => {
+                tracing::error!("unable to look for files: {err:?}");
+                return;
+            }
+        };
+        for err in scan.errors.iter() {
+            tracing::warn!(path = %err.path().display(), "unable to look for files: {err:?}");
+        }
+        // a partial scan doesn't tell whether the files are still matching
+        if scan.errors.is_empty() {
+            for (id, watcher) in self.state.watchers.iter_mut() {
+                watcher.found = scan.files.contains_key(id);
+            }
+        }
+        for (id, path) in scan.files {
+            if let Some(watcher) = self.state.watchers.get_mut(&id) {
+                if watcher.path != path {
+                    tracing::info!(from = %watcher.path.display(), to = %path.display(), "file renamed");
+                    watcher.path = path;
+                }
+                continue;
+            }
+            let offset = match self.state.checkpoints.get(&id) {
+                Some(checkpoint) => Some(checkpoint.offset),
+                None if self.state.first_scan && self.state.config.read_from == ReadFrom::End => {
+                    None
+                }
+                None => Some(0),
+            };
+            match Watcher::open(path.clone(), offset).await {
+                Ok(watcher) => {
+                    tracing::info!(path = %path.display(), offset = watcher.offset, "watching file");
+                    self.state.watchers.insert(id, watcher);
+                }
+                Err(err) => tracing::warn!(path = %path.display(), "unable to open file: {err:?}"),
+            }
+        }
+        self.state.first_scan = false;
+    }
+
+    fn event(&self, path: &Path, text: String) -> crate::event::Event {
+        EventLog::new(text)
+            .with_attribute(
+                self.state.config.path_field.clone(),
+                path.display().to_string(),
+            )
+            .into()
+    }
+
+    /// Reads every watched file, returns true when some files have more to read.
+    async fn read(&mut self, collector: &Collector) -> bool {
+        let mut more = false;
+        let mut lost = Vec::new();
+        let ids = self.state.watchers.keys().copied().collect::<Vec<_>>();
+        for id in ids {
+            let mut events = Vec::new();
+            let Some(watcher) = self.state.watchers.get_mut(&id) else {
+                continue;
+            };
+            let mut lines = Vec::new();
+            let max_line_bytes = self.state.config.max_line_bytes;
+            let finished = match watcher.read_lines(&mut lines, max_line_bytes).await {
+                Ok(finished) => finished,
+                Err(err) => {
+                    tracing::warn!(path = %watcher.path.display(), "unable to read file: {err:?}");
+                    true
+                }
+            };
+            more |= !finished;
+            let idle = lines.is_empty();
+            for line in lines {
+                match self.state.config.multiline {
+                    Some(ref multiline) => events.extend(watcher.aggregate(line, multiline)),
+                    None => events.push((line.text, line.end)),
+                }
+            }
+            // the last event is only complete once the next one starts, or after a delay
+            let timeout = self.state.config.multiline.as_ref().map(|m| m.timeout);
+            let expired = watcher
+                .pending
+                .as_ref()
+                .zip(timeout)
+                .is_some_and(|(pending, timeout)| pending.updated_at.elapsed() >= timeout);
+            if expired || (idle && !watcher.found) {
+                if let Some(pending) = watcher.pending.take() {
+                    events.push((pending.text, watcher.consumed()));
+                }
+            }
+            if idle && !watcher.found {
+                lost.push(id);
+            }
+            let path = watcher.path.clone();
+            for (text, offset) in events {
+                if let Err(err) = collector.send_default(self.event(&path, text)).await {
+                    tracing::error!("unable to send event: {err:?}");
+                }
+                if let Some(watcher) = self.state.watchers.get_mut(&id) {
+                    watcher.offset = offset;
+                }
+            }
+        }
+        for id in lost {
+            if let Some(watcher) = self.state.watchers.remove(&id) {
+                tracing::info!(path = %watcher.path.display(), "stop watching file");
+                self.state.checkpoints.insert(
+                    id,
+                    Checkpoint {
+                        id,
+                        path: watcher.path,
+                        offset: watcher.offset,
+                    },
+                );
+            }
+        }
+        more
+    }
+
+    async fn save_checkpoints(&mut self) {
+        for (id, watcher) in self.state.watchers.iter() {
+            self.state.checkpoints.insert(
+                *id,
+                Checkpoint {
+                    id: *id,
+                    path: watcher.path.clone(),
+                    offset: watcher.offset,
+                },
+            );
+        }
+        let mut gone = Vec::new();
+        for (id, checkpoint) in self.state.checkpoints.iter() {
+            if !self.state.watchers.contains_key(id) && !checkpoint.exists().await {
+                gone.push(*id);
+            }
+        }
+        for id in gone {
+            self.state.checkpoints.remove(&id);
+        }
+        let Some(ref path) = self.state.config.checkpoint_file else {
+            return;
+        };
+        let checkpoints = self.state.checkpoints.values().collect::<Vec<_>>();
+        if let Err(err) = write_checkpoints(path, &checkpoints).await {
+            tracing::error!("unable to write checkpoints: {err:?}");
+        }
+    }
+
+    /// Looks for new files, reads them and saves the offsets, returns true when some files
+    /// have more to read.
+    async fn poll(&mut self, collector: &Collector) -> bool {
+        self.discover().await;
+        let more = self.read(collector).await;
+        self.save_checkpoints().await;
+        more
+    }
+}
+
+impl super::Executable for Source<Running> {
+    async fn execute(
+        mut self,
+        collector: Collector,
+        shutdown: CancellationToken,
+    ) -> Result<(), ExecutionError> {
+        tracing::info!("starting");
+        loop {
+            let more = self.poll(&collector).await;
+            if shutdown.is_cancelled() {
+                break;
+            }
+            if more {
+                continue;
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(self.state.config.interval) => {}
+                _ = shutdown.cancelled() => break,
+            }
+        }
+        tracing::info!("stopping");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+
+    use crate::components::collector::Collector;
+    use crate::components::output::NamedOutput;
+    use crate::prelude::{create_channel, Receiver};
+    use crate::sources::Preparable;
+
+    fn directory(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("tiny-vector-file-source-{name}"));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    fn append(path: &Path, content: &str) {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+    }
+
+    async fn prepared(directory: &Path, extra: &str) -> super::Source<super::Running> {
+        let config: super::Config = toml::from_str(&format!(
+            r#"
+include = ["{0}/*.log"]
+checkpoint_file = "{0}/checkpoints.json"
+{extra}
+"#,
+            directory.display()
+        ))
+        .unwrap();
+        config.build().unwrap().prepare().await.unwrap()
+    }
+
+    async fn received(rx: &mut Receiver) -> Vec<String> {
+        let mut result = Vec::new();
+        while !rx.is_empty() {
+            let event = rx.recv().await.unwrap();
+            result.push(event.into_event_log().unwrap().message);
+        }
+        result
+    }
+
+    #[tokio::test]
+    async fn should_resume_from_checkpoints() {
+        let directory = directory("resume");
+        let path = directory.join("app.log");
+        append(&path, "first\nsecond\r\nthird");
+        let (tx, mut rx) = create_channel(10);
+        let collector = Collector::default().with_output(NamedOutput::Default, tx);
+
+        let mut source = prepared(&directory, "").await;
+        source.poll(&collector).await;
+        assert_eq!(received(&mut rx).await, vec!["first", "second"]);
+        append(&path, " line\n");
+        source.poll(&collector).await;
+        assert_eq!(received(&mut rx).await, vec!["third line"]);
+        drop(source);
+
+        append(&path, "fourth\n");
+        let mut source = prepared(&directory, "").await;
+        source.poll(&collector).await;
+        let event = rx.recv().await.unwrap().into_event_log().unwrap();
+        assert_eq!(event.message, "fourth");
+        assert_eq!(
+            event.attributes["file"].as_text(),
+            Some(path.display().to_string().as_str())
+        );
+        assert!(rx.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_follow_rotations() {
+        let directory = directory("rotation");
+        let path = directory.join("app.log");
+        append(&path, "first\n");
+        let (tx, mut rx) = create_channel(10);
+        let collector = Collector::default().with_output(NamedOutput::Default, tx);
+
+        let mut source = prepared(&directory, "").await;
+        source.poll(&collector).await;
+        assert_eq!(received(&mut rx).await, vec!["first"]);
+
+        // renamed out of the patterns, the remaining lines are still read
+        append(&path, "second\n");
+        std::fs::rename(&path, directory.join("app.log.1")).unwrap();
+        append(&path, "third\n");
+        source.poll(&collector).await;
+        let mut messages = received(&mut rx).await;
+        messages.sort();
+        assert_eq!(messages, vec!["second", "third"]);
+        // dropped once read until the end
+        source.poll(&collector).await;
+        assert_eq!(source.state.watchers.len(), 1);
+
+        // truncated in place
+        std::fs::write(&path, "").unwrap();
+        source.poll(&collector).await;
+        append(&path, "fourth\n");
+        source.poll(&collector).await;
+        assert_eq!(received(&mut rx).await, vec!["fourth"]);
+    }
+
+    #[tokio::test]
+    async fn should_merge_multiline_events() {
+        let directory = directory("multiline");
+        let path = directory.join("app.log");
+        append(
+            &path,
+            "[INFO] started\n[ERROR] failed\n  at main.rs:12\n  at lib.rs:3\n[INFO] retrying\n",
+        );
+        let (tx, mut rx) = create_channel(10);
+        let collector = Collector::default().with_output(NamedOutput::Default, tx);
+
+        let mut source = prepared(
+            &directory,
+            r#"multiline = { start_pattern = "^\\[", timeout = 0 }"#,
+        )
+        .await;
+        source.poll(&collector).await;
+        assert_eq!(
+            received(&mut rx).await,
+            vec![
+                "[INFO] started",
+                "[ERROR] failed\n  at main.rs:12\n  at lib.rs:3",
+                "[INFO] retrying"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_skip_long_lines() {
+        let directory = directory("long-lines");
+        let path = directory.join("app.log");
+        append(
+            &path, "first
+",
+        );
+        append(&path, &"a".repeat(20));
+        let (tx, mut rx) = create_channel(10);
+        let collector = Collector::default().with_output(NamedOutput::Default, tx);
+
+        let mut source = prepared(&directory, "max_line_bytes = 10").await;
+        source.poll(&collector).await;
+        append(
+            &path,
+            &format!(
+                "{}
+second
+",
+                "a".repeat(20)
+            ),
+        );
+        source.poll(&collector).await;
+        assert_eq!(received(&mut rx).await, vec!["first", "second"]);
+        let watcher = source.state.watchers.values().next().unwrap();
+        assert_eq!(watcher.consumed(), watcher.position);
+    }
+
+    #[tokio::test]
+    async fn should_keep_checkpoints_of_files_still_on_disk() {
+        let directory = directory("lost");
+        let path = directory.join("app.log");
+        append(
+            &path, "first
+",
+        );
+        let (tx, mut rx) = create_channel(10);
+        let collector = Collector::default().with_output(NamedOutput::Default, tx);
+
+        let mut source = prepared(&directory, "").await;
+        source.poll(&collector).await;
+        assert_eq!(received(&mut rx).await, vec!["first"]);
+
+        // like missed by a scan
+        for watcher in source.state.watchers.values_mut() {
+            watcher.found = false;
+        }
+        source.read(&collector).await;
+        source.save_checkpoints().await;
+        assert!(source.state.watchers.is_empty());
+        assert_eq!(source.state.checkpoints.len(), 1);
+
+        append(
+            &path, "second
+",
+        );
+        source.poll(&collector).await;
+        assert_eq!(received(&mut rx).await, vec!["second"]);
+
+        std::fs::remove_file(&path).unwrap();
+        source.poll(&collector).await;
+        source.poll(&collector).await;
+        assert!(source.state.checkpoints.is_empty());
+    }
+
+    #[test]
+    fn should_reject_invalid_patterns() {
+        let config: super::Config = toml::from_str(r#"include = ["[a-"]"#).unwrap();
+        assert!(config.build().is_err());
+        let config: super::Config = toml::from_str(r#"include = []"#).unwrap();
+        assert!(config.build().is_err());
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::components::collector::Collector;
+use crate::components::metrics::ComponentSnapshot;
+use crate::components::name::ComponentName;
+use crate::components::output::ComponentWithOutputs;
+use crate::components::ExecutionError;
+use crate::event::metric::{EventMetric, EventMetricValue};
+
+const NAMESPACE: &str = "tiny_vector";
+
+#[derive(Debug, thiserror::Error)]
+pub enum BuildError {}
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
+pub struct Config {
+    /// Interval between emitting events, in ms
+    pub interval: Option<u64>,
+}
+
+impl ComponentWithOutputs for Config {}
+
+impl Config {
+    pub fn build(self) -> Result<Source, BuildError> {
+        Ok(Source {
+            state: Stale {
+                duration: tokio::time::Duration::from_millis(self.interval.unwrap_or(1000)),
+            },
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum StartingError {}
+
+pub(crate) struct Stale {
+    duration: tokio::time::Duration,
+}
+
+pub(crate) struct Running {
+    timer: tokio::time::Interval,
+    /// Counters sent at the previous iteration, the counters are sent as increments
+    previous: HashMap<(ComponentName, &'static str), u64>,
+}
+
+pub struct Source<S = Stale> {
+    state: S,
+}
+
+impl<S> Source<S> {
+    pub const fn flavor(&self) -> &'static str {
+        "internal_metrics"
+    }
+}
+
+impl super::Preparable for Source<Stale> {
+    type Output = Source<Running>;
+    type Error = StartingError;
+
+    async fn prepare(self) -> Result<Self::Output, Self::Error> {
+        Ok(Source {
+            state: Running {
+                timer: tokio::time::interval(self.state.duration),
+                previous: Default::default(),
+            },
+        })
+    }
+}
+
+impl super::Executable for Source<Running> {
+    async fn execute(
+        mut self,
+        collector: Collector,
+        shutdown: CancellationToken,
+    ) -> Result<(), ExecutionError> {
+        tracing::info!("starting");
+        let result = 'root: loop {
+            tokio::select! {
+                _ = self.state.timer.tick() => {}
+                _ = shutdown.cancelled() => break Ok(()),
+            }
+            let snapshot = crate::components::metrics::snapshot();
+            for metric in self.iterate(snapshot) {
+                if let Err(err) = collector.send_default(metric.into()).await {
+                    tracing::error!("unable to send internal metric: {err:?}");
+                    break 'root Err(err.into());
+                }
+            }
+        };
+        tracing::info!("stopping");
+        result
+    }
+}
+
+impl Source<Running> {
+    fn counter(
+        &mut self,
+        instant: u64,
+        component: &ComponentSnapshot,
+        name: &'static str,
+        value: u64,
+    ) -> EventMetric {
+        let previous = self
+            .state
+            .previous
+            .insert((component.name.clone(), name), value)
+            .unwrap_or(0);
+        // the counters start from zero again when the component gets restarted
+        let increment = value.checked_sub(previous).unwrap_or(value);
+        Self::tagged(
+            EventMetric::new(
+                instant,
+                NAMESPACE,
+                name,
+                EventMetricValue::Counter(increment),
+            ),
+            component,
+        )
+    }
+
+    fn gauge(
+        instant: u64,
+        component: &ComponentSnapshot,
+        name: &'static str,
+        value: f64,
+    ) -> EventMetric {
+        Self::tagged(
+            EventMetric::new(instant, NAMESPACE, name, EventMetricValue::Gauge(value)),
+            component,
+        )
+    }
+
+    fn tagged(metric: EventMetric, component: &ComponentSnapshot) -> EventMetric {
+        metric
+            .with_tag("name", component.name.to_string())
+            .with_tag("kind", component.kind)
+            .with_tag("flavor", component.flavor)
+    }
+
+    fn iterate(&mut self, snapshot: Vec<ComponentSnapshot>) -> Vec<EventMetric> {
+        let instant = crate::helper::now();
+        let mut metrics = Vec::with_capacity(snapshot.len() * 5);
+        for component in snapshot.iter() {
+            if let Some(value) = component.events_in {
+                metrics.push(self.counter(instant, component, "events_in", value));
+            }
+            if let Some(value) = component.events_out {
+                metrics.push(self.counter(instant, component, "events_out", value));
+            }
+            metrics.push(self.counter(instant, component, "errors", component.errors));
+            if let Some(value) = component.dropped {
+                metrics.push(self.counter(instant, component, "dropped", value));
+            }
+            if let Some(value) = component.channel_utilization {
+                metrics.push(Self::gauge(
+                    instant,
+                    component,
+                    "channel_utilization",
+                    value,
+                ));
+            }
+        }
+        // forget about the components that have been stopped
+        self.state
+            .previous
+            .retain(|(name, _), _| snapshot.iter().any(|item| &item.name == name));
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Running, Source};
+    use crate::components::metrics::ComponentSnapshot;
+    use crate::components::name::ComponentName;
+    use crate::event::metric::EventMetricValue;
+
+    fn snapshot(events_in: u64, errors: u64) -> Vec<ComponentSnapshot> {
+        vec![ComponentSnapshot {
+            name: ComponentName::new("output"),
+            kind: "sink",
+            flavor: "console",
+            events_in: Some(events_in),
+            events_out: None,
+            errors,
+            dropped: Some(0),
+            channel_utilization: Some(0.5),
+        }]
+    }
+
+    #[tokio::test]
+    async fn should_send_counters_as_increments() {
+        let mut source = Source {
+            state: Running {
+                timer: tokio::time::interval(tokio::time::Duration::from_secs(1)),
+                previous: Default::default(),
+            },
+        };
+        let metrics = source.iterate(snapshot(10, 1));
+        assert_eq!(metrics.len(), 4);
+        let events_in = &metrics[0];
+        assert_eq!(events_in.header.name.to_string(), "tiny_vector.events_in");
+        assert_eq!(events_in.value, EventMetricValue::Counter(10));
+        assert_eq!(events_in.header.tags.get("name").unwrap(), "output");
+        assert_eq!(events_in.header.tags.get("kind").unwrap(), "sink");
+        assert_eq!(events_in.header.tags.get("flavor").unwrap(), "console");
+        assert_eq!(metrics[3].value, EventMetricValue::Gauge(0.5));
+
+        let metrics = source.iterate(snapshot(15, 1));
+        assert_eq!(metrics[0].value, EventMetricValue::Counter(5));
+        assert_eq!(metrics[1].value, EventMetricValue::Counter(0));
+        // the component has been restarted
+        let metrics = source.iterate(snapshot(3, 0));
+        assert_eq!(metrics[0].value, EventMetricValue::Counter(3));
+    }
+}
@@ -1,80 +1,165 @@
 #![allow(clippy::large_enum_variant)]
 
+use tokio_util::sync::CancellationToken;
+
 use crate::components::collector::Collector;
+use crate::components::metrics::COMPONENT_SPAN;
 use crate::components::name::ComponentName;
 use crate::components::output::{ComponentWithOutputs, NamedOutput};
+use crate::components::ExecutionError;
 
+#[cfg(any(
+    feature = "source-syslog",
+    feature = "source-tcp-server",
+    feature = "source-udp"
+))]
+pub mod decoding;
+#[cfg(feature = "source-file")]
+pub mod file;
+pub mod internal_metrics;
 pub mod random_logs;
 #[cfg(feature = "source-sysinfo")]
 pub mod sysinfo;
+#[cfg(feature = "source-syslog")]
+pub mod syslog;
 #[cfg(feature = "source-tcp-server")]
 pub mod tcp_server;
+#[cfg(feature = "source-udp")]
+pub mod udp;
 
-const COMPONENT_KIND: &str = "source";
+pub(crate) const COMPONENT_KIND: &str = "source";
 
 #[derive(Debug, thiserror::Error)]
 pub enum BuildError {
+    #[cfg(feature = "source-file")]
+    #[error(transparent)]
+    File(#[from] self::file::BuildError),
+    #[error(transparent)]
+    InternalMetrics(#[from] self::internal_metrics::BuildError),
     #[error(transparent)]
     RandomLogs(#[from] self::random_logs::BuildError),
+    #[cfg(feature = "source-syslog")]
+    #[error(transparent)]
+    Syslog(#[from] self::syslog::BuildError),
     #[cfg(feature = "source-sysinfo")]
     #[error(transparent)]
     Sysinfo(#[from] self::sysinfo::BuildError),
     #[cfg(feature = "source-tcp-server")]
     #[error(transparent)]
     TcpServer(#[from] self::tcp_server::BuildError),
+    #[cfg(feature = "source-udp")]
+    #[error(transparent)]
+    Udp(#[from] self::udp::BuildError),
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 #[enum_dispatch::enum_dispatch(ComponentWithOutputs)]
 pub enum Config {
+    #[cfg(feature = "source-file")]
+    File(self::file::Config),
+    InternalMetrics(self::internal_metrics::Config),
     RandomLogs(self::random_logs::Config),
+    #[cfg(feature = "source-syslog")]
+    Syslog(self::syslog::Config),
     #[cfg(feature = "source-sysinfo")]
     Sysinfo(self::sysinfo::Config),
     #[cfg(feature = "source-tcp-server")]
     TcpServer(self::tcp_server::Config),
+    #[cfg(feature = "source-udp")]
+    Udp(self::udp::Config),
 }
 
 impl Config {
+    /// Type of the source, as written in the configuration.
+    pub fn flavor(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "source-file")]
+            Self::File(_) => "file",
+            Self::InternalMetrics(_) => "internal_metrics",
+            Self::RandomLogs(_) => "random_logs",
+            #[cfg(feature = "source-syslog")]
+            Self::Syslog(_) => "syslog",
+            #[cfg(feature = "source-sysinfo")]
+            Self::Sysinfo(_) => "sysinfo",
+            #[cfg(feature = "source-tcp-server")]
+            Self::TcpServer(_) => "tcp_server",
+            #[cfg(feature = "source-udp")]
+            Self::Udp(_) => "udp",
+        }
+    }
+
     pub fn build(self) -> Result<Source, BuildError> {
         Ok(match self {
+            #[cfg(feature = "source-file")]
+            Self::File(inner) => Source::File(inner.build()?),
+            Self::InternalMetrics(inner) => Source::InternalMetrics(inner.build()?),
             Self::RandomLogs(inner) => Source::RandomLogs(inner.build()?),
+            #[cfg(feature = "source-syslog")]
+            Self::Syslog(inner) => Source::Syslog(inner.build()?),
             #[cfg(feature = "source-sysinfo")]
             Self::Sysinfo(inner) => Source::Sysinfo(inner.build()?),
             #[cfg(feature = "source-tcp-server")]
             Self::TcpServer(inner) => Source::TcpServer(inner.build()?),
+            #[cfg(feature = "source-udp")]
+            Self::Udp(inner) => Source::Udp(inner.build()?),
         })
     }
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum StartingError {
+    #[cfg(feature = "source-file")]
+    #[error(transparent)]
+    File(#[from] self::file::StartingError),
+    #[error(transparent)]
+    InternalMetrics(#[from] self::internal_metrics::StartingError),
     #[error(transparent)]
     RandomLogs(#[from] self::random_logs::StartingError),
+    #[cfg(feature = "source-syslog")]
+    #[error(transparent)]
+    Syslog(#[from] self::syslog::StartingError),
     #[cfg(feature = "source-sysinfo")]
     #[error(transparent)]
     Sysinfo(#[from] self::sysinfo::StartingError),
     #[cfg(feature = "source-tcp-server")]
     #[error(transparent)]
     TcpServer(#[from] self::tcp_server::StartingError),
+    #[cfg(feature = "source-udp")]
+    #[error(transparent)]
+    Udp(#[from] self::udp::StartingError),
 }
 
 pub enum Source {
+    #[cfg(feature = "source-file")]
+    File(self::file::Source),
+    InternalMetrics(self::internal_metrics::Source),
     RandomLogs(self::random_logs::Source),
+    #[cfg(feature = "source-syslog")]
+    Syslog(self::syslog::Source),
     #[cfg(feature = "source-sysinfo")]
     Sysinfo(self::sysinfo::Source),
     #[cfg(feature = "source-tcp-server")]
     TcpServer(self::tcp_server::Source),
+    #[cfg(feature = "source-udp")]
+    Udp(self::udp::Source),
 }
 
 impl Source {
     fn flavor(&self) -> &'static str {
         match self {
+            #[cfg(feature = "source-file")]
+            Self::File(inner) => inner.flavor(),
+            Self::InternalMetrics(inner) => inner.flavor(),
             Self::RandomLogs(inner) => inner.flavor(),
+            #[cfg(feature = "source-syslog")]
+            Self::Syslog(inner) => inner.flavor(),
             #[cfg(feature = "source-sysinfo")]
             Self::Sysinfo(inner) => inner.flavor(),
             #[cfg(feature = "source-tcp-server")]
             Self::TcpServer(inner) => inner.flavor(),
+            #[cfg(feature = "source-udp")]
+            Self::Udp(inner) => inner.flavor(),
         }
     }
 
@@ -82,19 +167,30 @@ impl Source {
         self,
         name: &ComponentName,
         collector: Collector,
-    ) -> Result<tokio::task::JoinHandle<()>, StartingError> {
+        shutdown: CancellationToken,
+    ) -> Result<tokio::task::JoinHandle<Result<(), ExecutionError>>, StartingError> {
+        let metrics =
+            crate::components::metrics::register(name, COMPONENT_KIND, self.flavor(), None);
+        let collector = collector.with_metrics(metrics).with_source(name);
         let span = tracing::info_span!(
-            "component",
+            COMPONENT_SPAN,
             name = name.as_ref(),
             kind = COMPONENT_KIND,
             flavor = self.flavor(),
         );
         Ok(match self {
-            Self::RandomLogs(inner) => run(inner, span, collector).await?,
+            #[cfg(feature = "source-file")]
+            Self::File(inner) => run(inner, span, collector, shutdown).await?,
+            Self::InternalMetrics(inner) => run(inner, span, collector, shutdown).await?,
+            Self::RandomLogs(inner) => run(inner, span, collector, shutdown).await?,
+            #[cfg(feature = "source-syslog")]
+            Self::Syslog(inner) => run(inner, span, collector, shutdown).await?,
             #[cfg(feature = "source-sysinfo")]
-            Self::Sysinfo(inner) => run(inner, span, collector).await?,
+            Self::Sysinfo(inner) => run(inner, span, collector, shutdown).await?,
             #[cfg(feature = "source-tcp-server")]
-            Self::TcpServer(inner) => run(inner, span, collector).await?,
+            Self::TcpServer(inner) => run(inner, span, collector, shutdown).await?,
+            #[cfg(feature = "source-udp")]
+            Self::Udp(inner) => run(inner, span, collector, shutdown).await?,
         })
     }
 }
@@ -108,7 +204,12 @@ trait Preparable {
 }
 
 trait Executable {
-    fn execute(self, collector: Collector) -> impl std::future::Future<Output = ()> + Send;
+    /// Produces events until the shutdown token gets cancelled.
+    fn execute(
+        self,
+        collector: Collector,
+        shutdown: CancellationToken,
+    ) -> impl std::future::Future<Output = Result<(), ExecutionError>> + Send;
 }
 
 async fn run<
@@ -119,11 +220,12 @@ async fn run<
     element: P,
     span: tracing::Span,
     collector: Collector,
-) -> Result<tokio::task::JoinHandle<()>, StartingError> {
+    shutdown: CancellationToken,
+) -> Result<tokio::task::JoinHandle<Result<(), ExecutionError>>, StartingError> {
     use tracing::Instrument;
 
     let prepared = element.prepare().await.map_err(|err| err.into())?;
     Ok(tokio::spawn(async move {
-        prepared.execute(collector).instrument(span).await
+        prepared.execute(collector, shutdown).instrument(span).await
     }))
 }
@@ -1,6 +1,8 @@
+use tokio_util::sync::CancellationToken;
+
 use crate::components::collector::Collector;
 use crate::components::output::ComponentWithOutputs;
-use crate::event::log::EventLogAttribute;
+use crate::components::ExecutionError;
 
 #[derive(Debug, thiserror::Error)]
 pub struct BuildError;
@@ -11,7 +13,7 @@ impl std::fmt::Display for BuildError {
     }
 }
 
-#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
 pub struct Config {
     /// Interval between emitting events, in ms
     pub interval: Option<u64>,
@@ -21,10 +23,7 @@ fn generate() -> crate::event::Event {
     crate::event::log::EventLog::new("Hello World!")
         .with_attribute("hostname", "fake-server")
         .with_attribute("ddsource", "tiny-vector")
-        .with_attribute(
-            "timestamp",
-            EventLogAttribute::UInteger(crate::helper::now()),
-        )
+        .with_timestamp(crate::event::timestamp::now())
         .into()
 }
 
@@ -75,16 +74,24 @@ impl super::Preparable for Source<Stale> {
 }
 
 impl super::Executable for Source<Running> {
-    async fn execute(mut self, collector: Collector) {
+    async fn execute(
+        mut self,
+        collector: Collector,
+        shutdown: CancellationToken,
+    ) -> Result<(), ExecutionError> {
         tracing::info!("starting");
-        loop {
-            let _ = self.state.timer.tick().await;
+        let result = loop {
+            tokio::select! {
+                _ = self.state.timer.tick() => {}
+                _ = shutdown.cancelled() => break Ok(()),
+            }
             tracing::debug!("generating new random log");
             if let Err(err) = collector.send_default(generate()).await {
                 tracing::error!("unable to send generated log: {err:?}");
-                break;
+                break Err(err.into());
             }
-        }
+        };
         tracing::info!("stopping");
+        result
     }
 }
@@ -1,9 +1,11 @@
 use std::collections::VecDeque;
 
 use sysinfo::{CpuRefreshKind, MemoryRefreshKind, RefreshKind, System};
+use tokio_util::sync::CancellationToken;
 
 use crate::components::collector::Collector;
 use crate::components::output::ComponentWithOutputs;
+use crate::components::ExecutionError;
 use crate::event::metric::{EventMetric, EventMetricValue};
 use crate::event::Event;
 
@@ -18,7 +20,7 @@ impl std::fmt::Display for BuildError {
     }
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 pub struct CpuConfig {
     #[serde(default = "crate::helper::default_true")]
     pub usage: bool,
@@ -49,7 +51,7 @@ impl CpuConfig {
     }
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 pub struct MemoryConfig {
     #[serde(default = "crate::helper::default_true")]
     pub ram: bool,
@@ -80,7 +82,7 @@ impl MemoryConfig {
     }
 }
 
-#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
 pub struct Config {
     /// Interval between emitting events, in ms
     pub interval: Option<u64>,
@@ -156,21 +158,29 @@ impl super::Preparable for Source<Stale> {
 }
 
 impl super::Executable for Source<Running> {
-    async fn execute(mut self, collector: Collector) {
+    async fn execute(
+        mut self,
+        collector: Collector,
+        shutdown: CancellationToken,
+    ) -> Result<(), ExecutionError> {
         tracing::info!("starting");
         let mut buffer = VecDeque::new();
-        'root: loop {
-            let _ = self.state.timer.tick().await;
+        let result = 'root: loop {
+            tokio::select! {
+                _ = self.state.timer.tick() => {}
+                _ = shutdown.cancelled() => break Ok(()),
+            }
             self.iterate(&mut buffer);
             while let Some(metric) = buffer.pop_front() {
                 let event = self.augment_metric(metric);
                 if let Err(error) = collector.send_default(event).await {
                     tracing::error!("unable to send generated log: {error:?}");
-                    break 'root;
+                    break 'root Err(error.into());
                 }
             }
-        }
+        };
         tracing::info!("stopping");
+        result
     }
 }
 
@@ -0,0 +1,281 @@
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+
+use tokio::net::{TcpListener, UdpSocket};
+use tokio_util::sync::CancellationToken;
+
+use super::decoding::{Decoding, Framing};
+use crate::components::collector::Collector;
+use crate::components::output::ComponentWithOutputs;
+use crate::components::ExecutionError;
+
+/// Longest datagram, as limited by UDP.
+const MAX_DATAGRAM_SIZE: usize = 65_535;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BuildError {
+    #[error("unable to parse address")]
+    InvalidAddress(#[source] std::net::AddrParseError),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    Tcp,
+    #[default]
+    Udp,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
+pub struct Config {
+    pub address: Option<String>,
+    #[serde(default)]
+    pub mode: Mode,
+    /// How the messages are delimited over TCP, octet counting with a fallback on newlines
+    /// when not specified
+    pub framing: Option<Framing>,
+}
+
+impl ComponentWithOutputs for Config {}
+
+impl Config {
+    pub fn build(self) -> Result<Source, BuildError> {
+        let address = match self.address {
+            Some(value) => value
+                .parse::<SocketAddr>()
+                .map_err(BuildError::InvalidAddress)?,
+            None => SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 514)),
+        };
+        Ok(Source {
+            state: Stale {
+                address,
+                mode: self.mode,
+                framing: self.framing.unwrap_or(Framing::OctetCounting),
+            },
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum StartingError {
+    #[error("unable to bind socket")]
+    UnableToBind(#[source] std::io::Error),
+}
+
+pub(crate) struct Stale {
+    address: SocketAddr,
+    mode: Mode,
+    framing: Framing,
+}
+
+enum Listener {
+    Tcp {
+        listener: TcpListener,
+        framing: Framing,
+    },
+    Udp(UdpSocket),
+}
+
+pub(crate) struct Running {
+    listener: Listener,
+}
+
+pub struct Source<S = Stale> {
+    state: S,
+}
+
+impl<S> Source<S> {
+    pub const fn flavor(&self) -> &'static str {
+        "syslog"
+    }
+}
+
+impl super::Preparable for Source<Stale> {
+    type Output = Source<Running>;
+    type Error = StartingError;
+
+    async fn prepare(self) -> Result<Source<Running>, StartingError> {
+        let listener = match self.state.mode {
+            Mode::Tcp => Listener::Tcp {
+                listener: TcpListener::bind(self.state.address)
+                    .await
+                    .map_err(StartingError::UnableToBind)?,
+                framing: self.state.framing,
+            },
+            Mode::Udp => Listener::Udp(
+                UdpSocket::bind(self.state.address)
+                    .await
+                    .map_err(StartingError::UnableToBind)?,
+            ),
+        };
+        Ok(Source {
+            state: Running { listener },
+        })
+    }
+}
+
+impl Source<Running> {
+    async fn iterate(
+        &self,
+        buffer: &mut [u8],
+        collector: &Collector,
+        shutdown: &CancellationToken,
+    ) -> std::io::Result<()> {
+        match self.state.listener {
+            Listener::Tcp {
+                ref listener,
+                framing,
+            } => {
+                let (stream, address) = listener.accept().await?;
+                let span = tracing::info_span!("connection", client = %address);
+                let (collector, shutdown) = (collector.clone(), shutdown.clone());
+                tokio::spawn(async move {
+                    let _entered = span.enter();
+                    if let Err(err) = super::decoding::read_stream(
+                        stream,
+                        Decoding::Syslog,
+                        framing,
+                        collector,
+                        shutdown,
+                    )
+                    .await
+                    {
+                        tracing::error!("connection failed: {err:?}");
+                    }
+                });
+            }
+            Listener::Udp(ref socket) => {
+                let (size, address) = socket.recv_from(buffer).await?;
+                match Decoding::Syslog.decode(&buffer[..size]) {
+                    Ok(event) => {
+                        if let Err(err) = collector.send_default(event).await {
+                            tracing::error!("unable to send message: {err:?}");
+                        }
+                    }
+                    Err(err) => {
+                        tracing::error!(client = %address, "invalid message received: {err:?}")
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl super::Executable for Source<Running> {
+    async fn execute(
+        self,
+        collector: Collector,
+        shutdown: CancellationToken,
+    ) -> Result<(), ExecutionError> {
+        tracing::info!("waiting for messages");
+        let mut buffer = vec![0; MAX_DATAGRAM_SIZE];
+        loop {
+            tokio::select! {
+                res = self.iterate(&mut buffer, &collector, &shutdown) => {
+                    if let Err(error) = res {
+                        tracing::error!("something went wrong: {error:?}");
+                    }
+                }
+                _ = shutdown.cancelled() => break,
+            }
+        }
+        tracing::info!("stopping");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::time::Duration;
+
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::{TcpStream, UdpSocket};
+
+    use super::Listener;
+    use crate::components::collector::Collector;
+    use crate::components::output::NamedOutput;
+    use crate::prelude::Receiver;
+    use crate::sources::{Executable, Preparable};
+
+    /// Starts the source on an available port, returns its address.
+    async fn start(config: &str) -> (SocketAddr, Receiver) {
+        let config: super::Config = toml::from_str(config).unwrap();
+        let (tx, rx) = crate::prelude::create_channel(10);
+        let collector = Collector::default().with_output(NamedOutput::Default, tx);
+        let source = config.build().unwrap().prepare().await.unwrap();
+        let address = match source.state.listener {
+            Listener::Tcp { ref listener, .. } => listener.local_addr(),
+            Listener::Udp(ref socket) => socket.local_addr(),
+        }
+        .unwrap();
+        tokio::spawn(source.execute(collector, Default::default()));
+        (address, rx)
+    }
+
+    async fn wait_for(rx: &Receiver, count: usize) {
+        for _ in 0..100 {
+            if rx.len() >= count {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("missing events");
+    }
+
+    #[tokio::test]
+    async fn should_receive_udp_messages() {
+        let (address, mut rx) = start(r#"address = "127.0.0.1:0""#).await;
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        client
+            .send_to(
+                b"<34>Oct 11 22:14:15 mymachine su: 'su root' failed\n",
+                address,
+            )
+            .await
+            .unwrap();
+        client.send_to(b"not syslog", address).await.unwrap();
+        client
+            .send_to(b"<165>1 - host app 42 - - hello", address)
+            .await
+            .unwrap();
+
+        wait_for(&rx, 2).await;
+        let event = rx.recv().await.unwrap().into_event_log().unwrap();
+        assert_eq!(event.message, "'su root' failed");
+        assert_eq!(event.attributes["severity"].as_text(), Some("crit"));
+        let event = rx.recv().await.unwrap().into_event_log().unwrap();
+        assert_eq!(event.message, "hello");
+        assert_eq!(event.attributes["procid"].as_text(), Some("42"));
+    }
+
+    #[tokio::test]
+    async fn should_receive_tcp_messages() {
+        let (address, mut rx) = start(
+            r#"
+address = "127.0.0.1:0"
+mode = "tcp"
+"#,
+        )
+        .await;
+        let mut client = TcpStream::connect(address).await.unwrap();
+        let message = "<14>1 - host app - - [meta seq=\"1\"] first";
+        client
+            .write_all(
+                format!(
+                    "{} {message}<14>Oct 11 22:14:15 host app: second\n",
+                    message.len()
+                )
+                .as_bytes(),
+            )
+            .await
+            .unwrap();
+
+        wait_for(&rx, 2).await;
+        let event = rx.recv().await.unwrap().into_event_log().unwrap();
+        assert_eq!(event.message, "first");
+        let event = rx.recv().await.unwrap().into_event_log().unwrap();
+        assert_eq!(event.message, "second");
+        assert_eq!(event.attributes["hostname"].as_text(), Some("host"));
+    }
+}
@@ -1,10 +1,12 @@
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
 
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::net::{TcpListener, TcpStream};
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
 
+use super::decoding::{Decoding, Framing};
 use crate::components::collector::Collector;
 use crate::components::output::ComponentWithOutputs;
+use crate::components::ExecutionError;
 
 #[derive(Debug, thiserror::Error)]
 pub enum BuildError {
@@ -12,9 +14,13 @@ pub enum BuildError {
     InvalidAddress(#[source] std::net::AddrParseError),
 }
 
-#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
 pub struct Config {
     pub address: Option<String>,
+    #[serde(default)]
+    pub decoding: Decoding,
+    #[serde(default)]
+    pub framing: Framing,
 }
 
 impl ComponentWithOutputs for Config {}
@@ -28,35 +34,13 @@ impl Config {
             None => SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 4000)),
         };
         Ok(Source {
-            state: Stale { address },
-        })
-    }
-}
-
-async fn handle_connection(stream: TcpStream, collector: Collector) -> std::io::Result<()> {
-    stream.readable().await?;
-    let mut reader = BufReader::new(stream);
-    loop {
-        let mut buffer = Vec::with_capacity(4096);
-        match reader.read_until(b'\n', &mut buffer).await {
-            Ok(0) => break,
-            Ok(n) => match serde_json::from_slice::<crate::event::Event>(&buffer[..n]) {
-                Ok(message) => {
-                    if let Err(err) = collector.send_default(message).await {
-                        tracing::error!("unable to send message: {err:?}");
-                    }
-                }
-                Err(err) => {
-                    tracing::error!("invalid message received: {err:?}")
-                }
+            state: Stale {
+                address,
+                decoding: self.decoding,
+                framing: self.framing,
             },
-            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                continue;
-            }
-            Err(err) => return Err(err),
-        }
+        })
     }
-    Ok(())
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -67,10 +51,14 @@ pub(crate) enum StartingError {
 
 pub(crate) struct Stale {
     address: SocketAddr,
+    decoding: Decoding,
+    framing: Framing,
 }
 
 pub(crate) struct Running {
     listener: TcpListener,
+    decoding: Decoding,
+    framing: Framing,
 }
 
 pub struct Source<S = Stale> {
@@ -87,7 +75,11 @@ impl Source<Stale> {
     #[cfg(test)]
     fn new(address: SocketAddr) -> Self {
         Self {
-            state: Stale { address },
+            state: Stale {
+                address,
+                decoding: Decoding::default(),
+                framing: Framing::default(),
+            },
         }
     }
 }
@@ -102,18 +94,29 @@ impl super::Preparable for Source<Stale> {
             .map_err(StartingError::UnableToBind)?;
 
         Ok(Source {
-            state: Running { listener },
+            state: Running {
+                listener,
+                decoding: self.state.decoding,
+                framing: self.state.framing,
+            },
         })
     }
 }
 
 impl Source<Running> {
-    async fn iterate(&self, collector: Collector) -> std::io::Result<()> {
+    async fn iterate(
+        &self,
+        collector: Collector,
+        shutdown: CancellationToken,
+    ) -> std::io::Result<()> {
         let (stream, address) = self.state.listener.accept().await?;
         let span = tracing::info_span!("connection", client = %address);
+        let (decoding, framing) = (self.state.decoding, self.state.framing);
         tokio::spawn(async move {
             let _entered = span.enter();
-            if let Err(err) = handle_connection(stream, collector).await {
+            if let Err(err) =
+                super::decoding::read_stream(stream, decoding, framing, collector, shutdown).await
+            {
                 tracing::error!("connection failed: {err:?}");
             }
         });
@@ -122,13 +125,24 @@ impl Source<Running> {
 }
 
 impl super::Executable for Source<Running> {
-    async fn execute(self, collector: Collector) {
+    async fn execute(
+        self,
+        collector: Collector,
+        shutdown: CancellationToken,
+    ) -> Result<(), ExecutionError> {
         tracing::info!("waiting for connections");
         loop {
-            if let Err(error) = self.iterate(collector.clone()).await {
-                tracing::error!("something went wrong: {error:?}");
+            tokio::select! {
+                res = self.iterate(collector.clone(), shutdown.clone()) => {
+                    if let Err(error) = res {
+                        tracing::error!("something went wrong: {error:?}");
+                    }
+                }
+                _ = shutdown.cancelled() => break,
             }
         }
+        tracing::info!("stopping");
+        Ok(())
     }
 }
 
@@ -142,6 +156,7 @@ mod tests {
 
     use crate::components::collector::Collector;
     use crate::components::output::NamedOutput;
+    use crate::sources::{Executable, Preparable};
 
     async fn wait_for(rx: &crate::prelude::Receiver) {
         for _ in 0..100 {
@@ -162,7 +177,13 @@ mod tests {
         let collector = Collector::default().with_output(NamedOutput::Default, tx);
         let source = super::Source::new(address);
 
-        let _handle = crate::sources::run(source, tracing::info_span!("foo"), collector).await;
+        let _handle = crate::sources::run(
+            source,
+            tracing::info_span!("foo"),
+            collector,
+            Default::default(),
+        )
+        .await;
 
         let mut client = TcpStream::connect(address).await.unwrap();
         let event = crate::event::Event::Log(crate::event::log::EventLog::new("Hello World!"));
@@ -182,7 +203,13 @@ mod tests {
         let collector = Collector::default().with_output(NamedOutput::Default, tx);
         let source = super::Source::new(address);
 
-        let _handle = crate::sources::run(source, tracing::info_span!("foo"), collector).await;
+        let _handle = crate::sources::run(
+            source,
+            tracing::info_span!("foo"),
+            collector,
+            Default::default(),
+        )
+        .await;
 
         let mut client = TcpStream::connect(address).await.unwrap();
 
@@ -206,4 +233,34 @@ mod tests {
 
         assert_eq!(rx.len(), 2);
     }
+
+    #[tokio::test]
+    async fn should_decode_with_framing() {
+        let config: super::Config = toml::from_str(
+            r#"
+address = "127.0.0.1:0"
+decoding = "syslog"
+framing = "octet_counting"
+"#,
+        )
+        .unwrap();
+        let (tx, mut rx) = crate::prelude::create_channel(10);
+        let collector = Collector::default().with_output(NamedOutput::Default, tx);
+
+        let source = config.build().unwrap().prepare().await.unwrap();
+        let address = source.state.listener.local_addr().unwrap();
+        let _handle = tokio::spawn(source.execute(collector, Default::default()));
+
+        let mut client = TcpStream::connect(address).await.unwrap();
+        let message = "<14>1 - host app - - - hello\nworld";
+        client
+            .write_all(format!("{} {message}", message.len()).as_bytes())
+            .await
+            .unwrap();
+
+        wait_for(&rx).await;
+        let event = rx.recv().await.unwrap().into_event_log().unwrap();
+        assert_eq!(event.message, "hello\nworld");
+        assert_eq!(event.attributes["appname"].as_text(), Some("app"));
+    }
 }
@@ -0,0 +1,210 @@
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+
+use tokio::net::UdpSocket;
+use tokio_util::sync::CancellationToken;
+
+use super::decoding::Decoding;
+use crate::components::collector::Collector;
+use crate::components::output::ComponentWithOutputs;
+use crate::components::ExecutionError;
+
+/// Longest datagram, as limited by UDP.
+const MAX_DATAGRAM_SIZE: usize = 65_535;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BuildError {
+    #[error("unable to parse address")]
+    InvalidAddress(#[source] std::net::AddrParseError),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
+pub struct Config {
+    pub address: Option<String>,
+    /// Each line of a datagram being decoded as an event
+    #[serde(default)]
+    pub decoding: Decoding,
+}
+
+impl ComponentWithOutputs for Config {}
+
+impl Config {
+    pub fn build(self) -> Result<Source, BuildError> {
+        let address = match self.address {
+            Some(value) => value
+                .parse::<SocketAddr>()
+                .map_err(BuildError::InvalidAddress)?,
+            None => SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 4000)),
+        };
+        Ok(Source {
+            state: Stale {
+                address,
+                decoding: self.decoding,
+            },
+        })
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum StartingError {
+    #[error("unable to bind socket")]
+    UnableToBind(#[source] std::io::Error),
+}
+
+pub(crate) struct Stale {
+    address: SocketAddr,
+    decoding: Decoding,
+}
+
+pub(crate) struct Running {
+    socket: UdpSocket,
+    decoding: Decoding,
+}
+
+pub struct Source<S = Stale> {
+    state: S,
+}
+
+impl<S> Source<S> {
+    pub const fn flavor(&self) -> &'static str {
+        "udp"
+    }
+}
+
+impl super::Preparable for Source<Stale> {
+    type Output = Source<Running>;
+    type Error = StartingError;
+
+    async fn prepare(self) -> Result<Source<Running>, StartingError> {
+        let socket = UdpSocket::bind(self.state.address)
+            .await
+            .map_err(StartingError::UnableToBind)?;
+        Ok(Source {
+            state: Running {
+                socket,
+                decoding: self.state.decoding,
+            },
+        })
+    }
+}
+
+impl Source<Running> {
+    async fn iterate(&self, buffer: &mut [u8], collector: &Collector) -> std::io::Result<()> {
+        let (size, address) = self.state.socket.recv_from(buffer).await?;
+        let lines = buffer[..size]
+            .split(|c| *c == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+            .filter(|line| !line.is_empty());
+        for line in lines {
+            match self.state.decoding.decode(line) {
+                Ok(event) => {
+                    if let Err(err) = collector.send_default(event).await {
+                        tracing::error!("unable to send message: {err:?}");
+                    }
+                }
+                Err(err) => {
+                    tracing::error!(client = %address, "invalid message received: {err:?}")
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl super::Executable for Source<Running> {
+    async fn execute(
+        self,
+        collector: Collector,
+        shutdown: CancellationToken,
+    ) -> Result<(), ExecutionError> {
+        tracing::info!("waiting for datagrams");
+        let mut buffer = vec![0; MAX_DATAGRAM_SIZE];
+        loop {
+            tokio::select! {
+                res = self.iterate(&mut buffer, &collector) => {
+                    if let Err(error) = res {
+                        tracing::error!("something went wrong: {error:?}");
+                    }
+                }
+                _ = shutdown.cancelled() => break,
+            }
+        }
+        tracing::info!("stopping");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::time::Duration;
+
+    use tokio::net::UdpSocket;
+
+    use crate::components::collector::Collector;
+    use crate::components::output::NamedOutput;
+    use crate::event::log::EventLog;
+    use crate::event::metric::EventMetricValue;
+    use crate::event::Event;
+    use crate::prelude::Receiver;
+    use crate::sources::{Executable, Preparable};
+
+    /// Starts the source on an available port, returns its address.
+    async fn start(config: &str) -> (SocketAddr, Receiver) {
+        let config: super::Config = toml::from_str(config).unwrap();
+        let (tx, rx) = crate::prelude::create_channel(10);
+        let collector = Collector::default().with_output(NamedOutput::Default, tx);
+        let source = config.build().unwrap().prepare().await.unwrap();
+        let address = source.state.socket.local_addr().unwrap();
+        tokio::spawn(source.execute(collector, Default::default()));
+        (address, rx)
+    }
+
+    async fn wait_for(rx: &Receiver, count: usize) {
+        for _ in 0..100 {
+            if rx.len() >= count {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("missing events");
+    }
+
+    #[tokio::test]
+    async fn should_receive_native_events() {
+        let (address, mut rx) = start(r#"address = "127.0.0.1:0""#).await;
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let event = Event::from(EventLog::new("hello world"));
+        let mut payload = serde_json::to_vec(&event).unwrap();
+        payload.extend_from_slice(b"\nnot json\n");
+        client.send_to(&payload, address).await.unwrap();
+
+        wait_for(&rx, 1).await;
+        let received = rx.recv().await.unwrap().into_event_log().unwrap();
+        assert_eq!(received.message, "hello world");
+        assert!(rx.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_receive_statsd_metrics() {
+        let (address, mut rx) = start(
+            r#"
+address = "127.0.0.1:0"
+decoding = "statsd"
+"#,
+        )
+        .await;
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        client
+            .send_to(b"page.views:2|c|#env:prod\nfuel.level:0.5|g\n", address)
+            .await
+            .unwrap();
+
+        wait_for(&rx, 2).await;
+        let metric = rx.recv().await.unwrap().into_event_metric().unwrap();
+        assert_eq!(metric.header.name.name, "page.views");
+        assert_eq!(metric.header.tags["env"], "prod");
+        assert_eq!(metric.value, EventMetricValue::Counter(2));
+        let metric = rx.recv().await.unwrap().into_event_metric().unwrap();
+        assert_eq!(metric.value, EventMetricValue::Gauge(0.5));
+    }
+}
@@ -0,0 +1,181 @@
+use std::path::Path;
+
+/// Format of a configuration file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    #[default]
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl Format {
+    /// Guesses the format from the extension of the file, falling back on TOML.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => Self::Yaml,
+            Some("json") => Self::Json,
+            _ => Self::Toml,
+        }
+    }
+
+    pub fn parse<T: serde::de::DeserializeOwned>(&self, content: &str) -> Result<T, ParseError> {
+        match self {
+            Self::Toml => {
+                toml::de::from_str(content).map_err(|err| ParseError::Toml(Box::new(err)))
+            }
+            Self::Yaml => serde_yaml::from_str(content).map_err(ParseError::Yaml),
+            Self::Json => serde_json::from_str(content).map_err(ParseError::Json),
+        }
+    }
+}
+
+/// Position in a file, starting at 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    pub(super) fn from_offset(content: &str, offset: usize) -> Self {
+        let before = &content[..offset.min(content.len())];
+        let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    /// Offset of the location in the content, the end of the content when out of it.
+    pub(super) fn to_offset(self, content: &str) -> usize {
+        let line_start = match self.line {
+            0 | 1 => 0,
+            line => match content.match_indices('\n').nth(line - 2) {
+                Some((index, _)) => index + 1,
+                None => return content.len(),
+            },
+        };
+        content[line_start..]
+            .char_indices()
+            .map(|(index, _)| line_start + index)
+            .chain(std::iter::once(content.len()))
+            .nth(self.column.saturating_sub(1))
+            .unwrap_or(content.len())
+    }
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ParseError {
+    #[error(transparent)]
+    Toml(Box<toml::de::Error>),
+    #[error(transparent)]
+    Yaml(serde_yaml::Error),
+    #[error(transparent)]
+    Json(serde_json::Error),
+}
+
+impl ParseError {
+    /// Where the error occurred in the parsed content, when known.
+    pub fn location(&self, content: &str) -> Option<Location> {
+        match self {
+            Self::Toml(inner) => inner
+                .span()
+                .map(|span| Location::from_offset(content, span.start)),
+            Self::Yaml(inner) => inner.location().map(|location| Location {
+                line: location.line(),
+                column: location.column(),
+            }),
+            Self::Json(inner) => Some(Location {
+                line: inner.line(),
+                column: inner.column(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use test_case::test_case;
+
+    use super::{Format, Location};
+    use crate::topology::Config;
+
+    #[test_case("config.toml", Format::Toml; "toml")]
+    #[test_case("config.yaml", Format::Yaml; "yaml")]
+    #[test_case("config.yml", Format::Yaml; "yml")]
+    #[test_case("config.json", Format::Json; "json")]
+    #[test_case("config", Format::Toml; "without extension")]
+    fn should_guess_format(path: &str, expected: Format) {
+        assert_eq!(Format::from_path(Path::new(path)), expected);
+    }
+
+    const TOML: &str = r#"
+[sources.generator]
+type = "random_logs"
+
+[sinks.output]
+type = "black_hole"
+inputs = ["generator"]
+buffer = { capacity = 10 }
+"#;
+
+    const YAML: &str = r#"
+sources:
+  generator:
+    type: random_logs
+sinks:
+  output:
+    type: black_hole
+    inputs:
+      - generator
+    buffer:
+      capacity: 10
+"#;
+
+    const JSON: &str = r#"{
+  "sources": {
+    "generator": { "type": "random_logs" }
+  },
+  "sinks": {
+    "output": {
+      "type": "black_hole",
+      "inputs": ["generator"],
+      "buffer": { "capacity": 10 }
+    }
+  }
+}"#;
+
+    #[test]
+    fn should_parse_the_same_config_in_every_format() {
+        let expected: Config = Format::Toml.parse(TOML).unwrap();
+        assert_eq!(Format::Yaml.parse::<Config>(YAML).unwrap(), expected);
+        assert_eq!(Format::Json.parse::<Config>(JSON).unwrap(), expected);
+        assert!(expected.validate().is_ok());
+    }
+
+    #[test_case(Format::Toml, "[sources.generator]\ntype = \"random_logs\"\ninterval = fast\n", 3, 12; "toml")]
+    #[test_case(Format::Yaml, "sources:\n  generator:\n    type: random_logs\n    interval: fast\n", 3, 5; "yaml")]
+    #[test_case(Format::Json, "{\n  \"sources\": {\n    \"generator\": {\n      \"type\": random_logs\n", 4, 15; "json")]
+    fn should_locate_errors(format: Format, content: &str, line: usize, column: usize) {
+        let error = format.parse::<Config>(content).unwrap_err();
+        assert_eq!(error.location(content), Some(Location { line, column }));
+    }
+
+    #[test_case("a = 1\nb = 2\n", 0; "start")]
+    #[test_case("a = 1\nb = 2\n", 8; "second line")]
+    #[test_case("a = \"é\"\nb = 2\n", 7; "after multibyte character")]
+    #[test_case("a = 1\nb = 2\n", 12; "end")]
+    fn should_convert_offsets(content: &str, offset: usize) {
+        let location = Location::from_offset(content, offset);
+        assert_eq!(location.to_offset(content), offset);
+    }
+}
@@ -0,0 +1,197 @@
+use std::fmt::Write;
+
+use super::Config;
+
+/// Language used to render the graph of the topology.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    #[default]
+    Dot,
+    Mermaid,
+}
+
+struct GraphNode<'a> {
+    name: &'a str,
+    kind: &'static str,
+    flavor: &'static str,
+}
+
+struct GraphEdge<'a> {
+    from: &'a str,
+    /// Only set for the named outputs
+    output: Option<String>,
+    to: &'a str,
+}
+
+impl Config {
+    fn graph_nodes(&self) -> Vec<GraphNode<'_>> {
+        let mut nodes = self
+            .sources
+            .iter()
+            .map(|(name, source)| (name, crate::sources::COMPONENT_KIND, source.inner.flavor()))
+            .chain(self.transforms.iter().map(|(name, transform)| {
+                let kind = crate::transforms::COMPONENT_KIND;
+                (name, kind, transform.inner.flavor())
+            }))
+            .chain(
+                self.sinks
+                    .iter()
+                    .map(|(name, sink)| (name, crate::sinks::COMPONENT_KIND, sink.inner.flavor())),
+            )
+            .map(|(name, kind, flavor)| GraphNode {
+                name: name.as_ref(),
+                kind,
+                flavor,
+            })
+            .collect::<Vec<_>>();
+        nodes.sort_by_key(|node| node.name);
+        nodes
+    }
+
+    fn graph_edges(&self) -> Vec<GraphEdge<'_>> {
+        let mut edges = self
+            .edges()
+            .map(|(input, name)| GraphEdge {
+                from: input.name.as_ref().as_ref(),
+                output: match input.output.is_default() {
+                    true => None,
+                    false => Some(format!("#{}", input.output)),
+                },
+                to: name.as_ref(),
+            })
+            .collect::<Vec<_>>();
+        edges.sort_by(|a, b| (a.from, &a.output, a.to).cmp(&(b.from, &b.output, b.to)));
+        edges
+    }
+
+    /// Renders the components and the edges between them, nodes are styled by kind
+    /// and labeled with their flavor.
+    pub fn to_graph(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Mermaid => self.to_mermaid(),
+        }
+    }
+
+    fn to_dot(&self) -> String {
+        let mut result = String::from("digraph topology {\n    rankdir=\"LR\";\n");
+        for node in self.graph_nodes() {
+            let (shape, color) = match node.kind {
+                crate::sources::COMPONENT_KIND => ("invhouse", "#c6e5ff"),
+                crate::transforms::COMPONENT_KIND => ("box", "#fff2c6"),
+                _ => ("house", "#d4f5d0"),
+            };
+            let _ = writeln!(
+                result,
+                "    \"{}\" [label=\"{}\\n({})\", shape=\"{shape}\", style=\"filled\", fillcolor=\"{color}\"];",
+                node.name, node.name, node.flavor
+            );
+        }
+        for edge in self.graph_edges() {
+            let _ = match edge.output {
+                Some(output) => writeln!(
+                    result,
+                    "    \"{}\" -> \"{}\" [label=\"{output}\"];",
+                    edge.from, edge.to
+                ),
+                None => writeln!(result, "    \"{}\" -> \"{}\";", edge.from, edge.to),
+            };
+        }
+        result.push_str("}\n");
+        result
+    }
+
+    fn to_mermaid(&self) -> String {
+        let nodes = self.graph_nodes();
+        // component names can contain characters with a meaning in mermaid, like "-"
+        let id = |name: &str| {
+            let index = nodes.iter().position(|node| node.name == name);
+            format!("n{}", index.unwrap_or_default())
+        };
+        let mut result = String::from("flowchart LR\n");
+        for (index, node) in nodes.iter().enumerate() {
+            let (open, close) = match node.kind {
+                crate::sources::COMPONENT_KIND => ("([", "])"),
+                crate::transforms::COMPONENT_KIND => ("[", "]"),
+                _ => ("[(", ")]"),
+            };
+            let _ = writeln!(
+                result,
+                "    n{index}{open}\"{}<br/>({})\"{close}:::{}",
+                node.name, node.flavor, node.kind
+            );
+        }
+        for edge in self.graph_edges() {
+            let (from, to) = (id(edge.from), id(edge.to));
+            let _ = match edge.output {
+                // "#" starts an entity code in mermaid labels
+                Some(output) => writeln!(
+                    result,
+                    "    {from} -->|\"{}\"| {to}",
+                    output.replace('#', "#35;")
+                ),
+                None => writeln!(result, "    {from} --> {to}"),
+            };
+        }
+        result.push_str("    classDef source fill:#c6e5ff\n");
+        result.push_str("    classDef transform fill:#fff2c6\n");
+        result.push_str("    classDef sink fill:#d4f5d0\n");
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GraphFormat;
+    use crate::topology::Config;
+
+    const CONFIG: &str = r#"
+[sources.generator]
+type = "random_logs"
+
+[transforms.router]
+type = "route"
+inputs = ["generator"]
+routes = {}
+
+[sinks.output]
+type = "black_hole"
+inputs = ["router#dropped"]
+"#;
+
+    #[test]
+    fn should_render_dot() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        assert_eq!(
+            config.to_graph(GraphFormat::Dot),
+            r##"digraph topology {
+    rankdir="LR";
+    "generator" [label="generator\n(random_logs)", shape="invhouse", style="filled", fillcolor="#c6e5ff"];
+    "output" [label="output\n(black_hole)", shape="house", style="filled", fillcolor="#d4f5d0"];
+    "router" [label="router\n(route)", shape="box", style="filled", fillcolor="#fff2c6"];
+    "generator" -> "router";
+    "router" -> "output" [label="#dropped"];
+}
+"##
+        );
+    }
+
+    #[test]
+    fn should_render_mermaid() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        assert_eq!(
+            config.to_graph(GraphFormat::Mermaid),
+            r##"flowchart LR
+    n0(["generator<br/>(random_logs)"]):::source
+    n1[("output<br/>(black_hole)")]:::sink
+    n2["router<br/>(route)"]:::transform
+    n0 --> n2
+    n2 -->|"#35;dropped"| n1
+    classDef source fill:#c6e5ff
+    classDef transform fill:#fff2c6
+    classDef sink fill:#d4f5d0
+"##
+        );
+    }
+}
@@ -0,0 +1,435 @@
+use std::ops::Range;
+use std::path::PathBuf;
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+
+use super::format::{Format, Location};
+
+/// Matches `$${`, used to write a literal `${`, and the `${...}` references.
+static REFERENCE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$\$\{|\$\{([^}]*)\}").unwrap());
+static VARIABLE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-zA-Z_][a-zA-Z0-9_]*$").unwrap());
+
+const FILE_PREFIX: &str = "file:";
+
+#[derive(Debug, thiserror::Error)]
+pub enum InterpolationError {
+    #[error("the environment variable {name} is not defined, at {location}")]
+    MissingVariable { name: String, location: Location },
+    #[error("invalid reference ${{{reference}}}, at {location}")]
+    InvalidReference {
+        reference: String,
+        location: Location,
+    },
+    #[error("unable to read secret file {path:?}, at {location}")]
+    UnableToReadSecret {
+        path: PathBuf,
+        location: Location,
+        #[source]
+        cause: std::io::Error,
+    },
+    #[error("the value of ${{{reference}}} cannot be written there, at {location}, a double quoted string accepts any value")]
+    UnescapableValue {
+        reference: String,
+        location: Location,
+    },
+}
+
+/// Where a reference is written, deciding how its value gets escaped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Context {
+    /// Outside of any string, the value is written as is, like a number
+    Plain,
+    Comment,
+    DoubleQuoted {
+        multiline: bool,
+    },
+    SingleQuoted {
+        multiline: bool,
+    },
+}
+
+/// Follows the strings and comments of a configuration file, without parsing it.
+struct Scanner {
+    format: Format,
+    context: Context,
+    position: usize,
+    /// Last character of the current line outside of the strings, other than a space
+    previous: Option<char>,
+}
+
+impl Scanner {
+    fn new(format: Format) -> Self {
+        Self {
+            format,
+            context: Context::Plain,
+            position: 0,
+            previous: None,
+        }
+    }
+
+    /// In YAML, quotes only start a string at the beginning of a scalar.
+    fn starts_scalar(&self) -> bool {
+        match self.format {
+            Format::Yaml => matches!(
+                self.previous,
+                None | Some(':' | '-' | '[' | '{' | ',' | '?')
+            ),
+            Format::Toml | Format::Json => true,
+        }
+    }
+
+    /// Reads the content up to the offset, returning the context at this offset.
+    fn advance(&mut self, content: &str, offset: usize) -> Context {
+        while self.position < offset {
+            let rest = &content[self.position..];
+            let Some(current) = rest.chars().next() else {
+                break;
+            };
+            let mut length = current.len_utf8();
+            match self.context {
+                Context::Plain => match current {
+                    '\n' => self.previous = None,
+                    ' ' | '\t' | '\r' => {}
+                    '#' if self.format == Format::Toml => self.context = Context::Comment,
+                    // a `#` inside a plain scalar, like an url fragment, doesn't start a comment
+                    '#' if self.format == Format::Yaml
+                        && (self.position == 0
+                            || content[..self.position].ends_with([' ', '\t', '\n'])) =>
+                    {
+                        self.context = Context::Comment
+                    }
+                    '"' if self.starts_scalar() => {
+                        let multiline = self.format == Format::Toml && rest.starts_with("\"\"\"");
+                        if multiline {
+                            length = 3;
+                        }
+                        self.context = Context::DoubleQuoted { multiline };
+                    }
+                    '\'' if self.format != Format::Json && self.starts_scalar() => {
+                        let multiline = self.format == Format::Toml && rest.starts_with("'''");
+                        if multiline {
+                            length = 3;
+                        }
+                        self.context = Context::SingleQuoted { multiline };
+                    }
+                    other => self.previous = Some(other),
+                },
+                Context::Comment => {
+                    if current == '\n' {
+                        self.context = Context::Plain;
+                        self.previous = None;
+                    }
+                }
+                Context::DoubleQuoted { multiline } => match current {
+                    '\\' => length += rest[1..].chars().next().map_or(0, char::len_utf8),
+                    '"' if multiline && rest.starts_with("\"\"\"") => {
+                        length = 3;
+                        self.context = Context::Plain;
+                        self.previous = Some('"');
+                    }
+                    '"' if !multiline => {
+                        self.context = Context::Plain;
+                        self.previous = Some('"');
+                    }
+                    _ => {}
+                },
+                Context::SingleQuoted { multiline } => match current {
+                    '\'' if self.format == Format::Yaml && rest.starts_with("''") => length = 2,
+                    '\'' if multiline && rest.starts_with("'''") => {
+                        length = 3;
+                        self.context = Context::Plain;
+                        self.previous = Some('\'');
+                    }
+                    '\'' if !multiline => {
+                        self.context = Context::Plain;
+                        self.previous = Some('\'');
+                    }
+                    _ => {}
+                },
+            }
+            self.position += length;
+        }
+        self.context
+    }
+}
+
+/// Escapes a value for where it gets written, nothing being returned when it cannot be.
+fn escape(value: &str, context: Context, format: Format) -> Option<String> {
+    match context {
+        Context::Plain | Context::Comment => {
+            // a new line would let the value add keys to the configuration
+            (!value.contains(['\n', '\r'])).then(|| value.to_string())
+        }
+        Context::DoubleQuoted { .. } => {
+            let mut result = String::with_capacity(value.len());
+            for c in value.chars() {
+                match c {
+                    '"' => result.push_str("\\\""),
+                    '\\' => result.push_str("\\\\"),
+                    '\n' => result.push_str("\\n"),
+                    '\r' => result.push_str("\\r"),
+                    '\t' => result.push_str("\\t"),
+                    c if c.is_control() => result.push_str(&format!("\\u{:04x}", c as u32)),
+                    c => result.push(c),
+                }
+            }
+            Some(result)
+        }
+        Context::SingleQuoted { multiline } => match format {
+            Format::Yaml if !value.contains(['\n', '\r']) => Some(value.replace('\'', "''")),
+            Format::Toml if multiline && !value.contains("'''") => Some(value.to_string()),
+            Format::Toml if !multiline && !value.contains(['\'', '\n', '\r']) => {
+                Some(value.to_string())
+            }
+            _ => None,
+        },
+    }
+}
+
+fn resolve<E>(content: &str, captures: &Captures, env: &E) -> Result<String, InterpolationError>
+where
+    E: Fn(&str) -> Option<String>,
+{
+    let Some(reference) = captures.get(1) else {
+        return Ok("${".to_string());
+    };
+    let location = || Location::from_offset(content, reference.start() - 2);
+    let reference = reference.as_str().trim();
+    if let Some(path) = reference.strip_prefix(FILE_PREFIX) {
+        let path = PathBuf::from(path.trim());
+        let secret = std::fs::read_to_string(&path).map_err(|cause| {
+            InterpolationError::UnableToReadSecret {
+                path,
+                location: location(),
+                cause,
+            }
+        })?;
+        // secrets are usually written with a trailing new line
+        return Ok(secret.trim_end_matches(['\r', '\n']).to_string());
+    }
+    let (name, default_value) = match reference.split_once(":-") {
+        Some((name, default_value)) => (name, Some(default_value)),
+        None => (reference, None),
+    };
+    if !VARIABLE_REGEX.is_match(name) {
+        return Err(InterpolationError::InvalidReference {
+            reference: reference.to_string(),
+            location: location(),
+        });
+    }
+    match (env(name), default_value) {
+        (Some(value), Some(_)) if !value.is_empty() => Ok(value),
+        (Some(value), None) => Ok(value),
+        (_, Some(default_value)) => Ok(default_value.to_string()),
+        (None, None) => Err(InterpolationError::MissingVariable {
+            name: name.to_string(),
+            location: location(),
+        }),
+    }
+}
+
+/// Part of the file replaced by the value of a reference.
+#[derive(Debug)]
+struct Replacement {
+    original: Range<usize>,
+    interpolated: Range<usize>,
+}
+
+/// Content of a configuration file with its references replaced.
+#[derive(Debug)]
+pub struct Interpolated {
+    pub content: String,
+    replacements: Vec<Replacement>,
+}
+
+impl Interpolated {
+    /// Converts a location in the interpolated content into a location in the original one,
+    /// a location inside a replaced value pointing at its reference.
+    pub fn original_location(&self, original: &str, location: Location) -> Location {
+        let offset = location.to_offset(&self.content);
+        let offset = match self
+            .replacements
+            .iter()
+            .rev()
+            .find(|item| item.interpolated.start <= offset)
+        {
+            Some(item) if offset < item.interpolated.end => item.original.start,
+            Some(item) => item.original.end + (offset - item.interpolated.end),
+            None => offset,
+        };
+        Location::from_offset(original, offset)
+    }
+}
+
+/// Replaces the `${VAR}`, `${VAR:-default}` and `${file:/path/to/secret}` references of a
+/// configuration file, before parsing it.
+///
+/// The default value is used when the variable is not defined or empty, `$${` is replaced
+/// by a literal `${`. The values are escaped for the string they're written in and the
+/// references in the comments are left as they are.
+pub fn interpolate<E>(
+    content: &str,
+    format: Format,
+    env: E,
+) -> Result<Interpolated, InterpolationError>
+where
+    E: Fn(&str) -> Option<String>,
+{
+    let mut result = String::with_capacity(content.len());
+    let mut replacements = Vec::new();
+    let mut scanner = Scanner::new(format);
+    let mut last = 0;
+    for captures in REFERENCE_REGEX.captures_iter(content) {
+        let matched = captures.get(0).expect("whole match");
+        let context = scanner.advance(content, matched.start());
+        scanner.position = matched.end();
+        if context == Context::Comment {
+            continue;
+        }
+        let value = resolve(content, &captures, &env)?;
+        let value = match captures.get(1) {
+            Some(reference) => escape(&value, context, format).ok_or_else(|| {
+                InterpolationError::UnescapableValue {
+                    reference: reference.as_str().trim().to_string(),
+                    location: Location::from_offset(content, matched.start()),
+                }
+            })?,
+            None => value,
+        };
+        result.push_str(&content[last..matched.start()]);
+        let start = result.len();
+        result.push_str(&value);
+        replacements.push(Replacement {
+            original: matched.range(),
+            interpolated: start..result.len(),
+        });
+        last = matched.end();
+    }
+    result.push_str(&content[last..]);
+    Ok(Interpolated {
+        content: result,
+        replacements,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::{interpolate, InterpolationError};
+    use crate::topology::format::{Format, Location};
+
+    fn env(name: &str) -> Option<String> {
+        match name {
+            "HOST" => Some("localhost".into()),
+            "EMPTY" => Some(String::new()),
+            "QUOTED" => Some("say \"hi\"\\\n".into()),
+            "MULTILINE" => Some("first\nsecond = true".into()),
+            _ => None,
+        }
+    }
+
+    fn toml(content: &str) -> String {
+        interpolate(content, Format::Toml, env).unwrap().content
+    }
+
+    #[test]
+    fn should_replace_variables() {
+        assert_eq!(
+            toml("url = \"http://${HOST}:${PORT:-8080}/$$path\""),
+            "url = \"http://localhost:8080/$$path\""
+        );
+        assert_eq!(toml("${EMPTY:-fallback}"), "fallback");
+        assert_eq!(toml("${EMPTY}"), "");
+        assert_eq!(toml("cost = \"$5\""), "cost = \"$5\"");
+        assert_eq!(toml("value = \"$${HOST}\""), "value = \"${HOST}\"");
+    }
+
+    #[test]
+    fn should_fail_with_missing_variable() {
+        let error = interpolate(
+            "[sinks.output]\ntoken = \"${API_TOKEN}\"\n",
+            Format::Toml,
+            env,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            &error,
+            InterpolationError::MissingVariable { name, location }
+                if name == "API_TOKEN" && *location == Location { line: 2, column: 10 }
+        ));
+        assert!(error.to_string().contains("API_TOKEN"));
+    }
+
+    #[test]
+    fn should_fail_with_invalid_reference() {
+        let error = interpolate("${not a variable}", Format::Toml, env).unwrap_err();
+        assert!(matches!(error, InterpolationError::InvalidReference { .. }));
+    }
+
+    #[test_case(Format::Toml, "# uses ${MISSING}\nhost = \"${HOST}\" # not ${MISSING}\n"; "toml")]
+    #[test_case(Format::Yaml, "# uses ${MISSING}\nhost: ${HOST} # not ${MISSING}\nurl: http://a#${HOST}\n"; "yaml")]
+    fn should_ignore_comments(format: Format, content: &str) {
+        let interpolated = interpolate(content, format, env).unwrap().content;
+        assert_eq!(interpolated.matches("${MISSING}").count(), 2);
+        assert!(interpolated.contains("localhost"));
+        assert!(!interpolated.contains("${HOST}"));
+    }
+
+    #[test_case(Format::Toml, "value = \"${QUOTED}\"", "value = \"say \\\"hi\\\"\\\\\\n\""; "toml basic string")]
+    #[test_case(Format::Json, "{\"value\": \"${QUOTED}\"}", "{\"value\": \"say \\\"hi\\\"\\\\\\n\"}"; "json string")]
+    #[test_case(Format::Yaml, "value: 'it''s ${HOST}'", "value: 'it''s localhost'"; "yaml single quoted")]
+    #[test_case(Format::Yaml, "value: it's \"${HOST}\"", "value: it's \"localhost\""; "yaml plain scalar with quotes")]
+    fn should_escape_values(format: Format, content: &str, expected: &str) {
+        assert_eq!(interpolate(content, format, env).unwrap().content, expected);
+    }
+
+    #[test]
+    fn should_keep_the_values_in_their_string() {
+        let content = "[sources.generator]\ntype = \"random_logs\"\n\n[sinks.output]\ntype = \"file\"\npath = \"${QUOTED}\"\ninputs = [\"generator\"]\n";
+        let interpolated = interpolate(content, Format::Toml, env).unwrap().content;
+        let config: crate::topology::Config = Format::Toml.parse(&interpolated).unwrap();
+        assert_eq!(config.sinks.len(), 1);
+    }
+
+    #[test_case(Format::Toml, "value = ${MULTILINE}"; "new line outside of a string")]
+    #[test_case(Format::Toml, "value = '${QUOTED}'"; "quote in toml literal string")]
+    fn should_reject_unescapable_values(format: Format, content: &str) {
+        let error = interpolate(content, format, env).unwrap_err();
+        assert!(matches!(error, InterpolationError::UnescapableValue { .. }));
+    }
+
+    #[test]
+    fn should_locate_in_original_content() {
+        let original = "a = \"${HOST}\"\nb = \"${EMPTY}\"\nc = oops";
+        let interpolated = interpolate(original, Format::Toml, env).unwrap();
+        let location = Location::from_offset(
+            &interpolated.content,
+            interpolated.content.find("oops").unwrap(),
+        );
+        assert_eq!(
+            interpolated.original_location(original, location),
+            Location { line: 3, column: 5 }
+        );
+        let inside = Location::from_offset(&interpolated.content, 7);
+        assert_eq!(
+            interpolated.original_location(original, inside),
+            Location { line: 1, column: 6 }
+        );
+    }
+
+    #[test]
+    fn should_read_secret_files() {
+        let path = std::env::temp_dir().join("tiny-vector-should-read-secret-files");
+        std::fs::write(&path, "s3cr3t\n").unwrap();
+        let content = format!("token = \"${{file:{}}}\"", path.display());
+        assert_eq!(toml(&content), "token = \"s3cr3t\"");
+        std::fs::remove_file(&path).unwrap();
+        let error = interpolate(&content, Format::Toml, env).unwrap_err();
+        assert!(matches!(
+            error,
+            InterpolationError::UnableToReadSecret { path: missing, .. } if missing == path
+        ));
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::{Config, Format, LoadingError, MergeConflict};
+use crate::components::name::ComponentName;
+
+const EXTENSIONS: [&str; 4] = ["toml", "yaml", "yml", "json"];
+
+fn is_pattern(path: &Path) -> bool {
+    path.to_str()
+        .map(|value| value.contains(['*', '?', '[']))
+        .unwrap_or(false)
+}
+
+/// Lists the configuration files of a directory, ignoring the files with an unknown extension.
+fn read_directory(path: &Path) -> Result<Vec<PathBuf>, LoadingError> {
+    let entries = std::fs::read_dir(path).map_err(|cause| LoadingError::UnableToRead {
+        path: path.to_path_buf(),
+        cause,
+    })?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|cause| LoadingError::UnableToRead {
+            path: path.to_path_buf(),
+            cause,
+        })?;
+        let file = entry.path();
+        let known = file
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| EXTENSIONS.contains(&ext))
+            .unwrap_or(false);
+        if known && file.is_file() {
+            files.push(file);
+        }
+    }
+    Ok(files)
+}
+
+fn read_pattern(pattern: &Path) -> Result<Vec<PathBuf>, LoadingError> {
+    let pattern = pattern.to_string_lossy();
+    let paths = glob::glob(&pattern).map_err(|cause| LoadingError::InvalidPattern {
+        pattern: pattern.to_string(),
+        cause,
+    })?;
+    let mut files = Vec::new();
+    for path in paths {
+        let path = path.map_err(|err| LoadingError::UnableToRead {
+            path: err.path().to_path_buf(),
+            cause: err.into(),
+        })?;
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// Lists the files behind a path, which can be a file, a directory or a glob pattern.
+///
+/// The files are sorted so that the merge order doesn't depend on the filesystem.
+fn expand(path: &Path) -> Result<Vec<PathBuf>, LoadingError> {
+    let mut files = if is_pattern(path) {
+        read_pattern(path)?
+    } else if path.is_dir() {
+        read_directory(path)?
+    } else {
+        return Ok(vec![path.to_path_buf()]);
+    };
+    if files.is_empty() {
+        return Err(LoadingError::NoFileFound {
+            path: path.to_path_buf(),
+        });
+    }
+    files.sort();
+    Ok(files)
+}
+
+impl Config {
+    /// Loads all the given files, directories and glob patterns, and merges them into
+    /// a single configuration.
+    pub fn from_paths<P: AsRef<Path>>(
+        paths: impl IntoIterator<Item = P>,
+        format: Option<Format>,
+    ) -> Result<Self, LoadingError> {
+        let mut config = Config::default();
+        // file where each component has been defined, to report duplicates
+        let mut origins: HashMap<ComponentName, PathBuf> = HashMap::new();
+        let mut data_dir_origin: Option<PathBuf> = None;
+        let mut loaded = HashSet::new();
+        for path in paths {
+            for file in expand(path.as_ref())? {
+                // a file can be matched by several paths
+                if !loaded.insert(file.canonicalize().unwrap_or_else(|_| file.clone())) {
+                    continue;
+                }
+                let fragment = Self::from_path(&file, format)?;
+                let names = fragment
+                    .components()
+                    .map(|(name, _)| name.clone())
+                    .collect::<Vec<_>>();
+                let has_data_dir = fragment.data_dir.is_some();
+                config.merge(fragment).map_err(|conflict| match conflict {
+                    MergeConflict::Component(name) => LoadingError::DuplicatedComponent {
+                        first: origins.get(&name).cloned().unwrap_or_default(),
+                        second: file.clone(),
+                        name,
+                    },
+                    MergeConflict::DataDir => LoadingError::DuplicatedDataDir {
+                        first: data_dir_origin.clone().unwrap_or_default(),
+                        second: file.clone(),
+                    },
+                })?;
+                origins.extend(names.into_iter().map(|name| (name, file.clone())));
+                if has_data_dir {
+                    data_dir_origin = Some(file.clone());
+                }
+            }
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::components::name::ComponentName;
+    use crate::topology::{Config, LoadingError};
+
+    fn directory(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("tiny-vector-{name}"));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        for (file, content) in files {
+            std::fs::write(root.join(file), content).unwrap();
+        }
+        root
+    }
+
+    const SOURCES: &str = "[sources.generator]\ntype = \"random_logs\"\n";
+    const TRANSFORMS: &str =
+        "transforms:\n  router:\n    type: route\n    routes: {}\n    inputs: [generator]\n";
+    const SINKS: &str =
+        r#"{"sinks": {"output": {"type": "black_hole", "inputs": ["router#dropped"]}}}"#;
+
+    #[test]
+    fn should_load_directory() {
+        let root = directory(
+            "should-load-directory",
+            &[
+                ("sources.toml", SOURCES),
+                ("transforms.yaml", TRANSFORMS),
+                ("sinks.json", SINKS),
+                ("README.md", "not a configuration file"),
+            ],
+        );
+        let config = Config::from_paths([&root], None).unwrap();
+        assert_eq!(config.components().count(), 3);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn should_load_glob_pattern() {
+        let root = directory(
+            "should-load-glob-pattern",
+            &[
+                ("sources.toml", SOURCES),
+                ("transforms.yaml", TRANSFORMS),
+                ("sinks.json", SINKS),
+            ],
+        );
+        let config = Config::from_paths([root.join("s*.*")], None).unwrap();
+        assert!(config.sources.contains_key("generator"));
+        assert!(config.sinks.contains_key("output"));
+        assert!(!config.transforms.contains_key("router"));
+        // the files matched by several paths are only loaded once
+        let config =
+            Config::from_paths([root.join("sources.toml"), root.join("*.toml")], None).unwrap();
+        assert_eq!(config.components().count(), 1);
+    }
+
+    #[test]
+    fn should_report_both_files_defining_a_component() {
+        let root = directory(
+            "should-report-both-files",
+            &[
+                ("first.toml", SOURCES),
+                (
+                    "second.yaml",
+                    "sources:\n  generator:\n    type: random_logs\n",
+                ),
+            ],
+        );
+        let error = Config::from_paths([&root], None).unwrap_err();
+        assert!(matches!(
+            &error,
+            LoadingError::DuplicatedComponent { name, first, second }
+                if *name == ComponentName::new("generator")
+                    && first == &root.join("first.toml")
+                    && second == &root.join("second.yaml")
+        ));
+        assert!(error.to_string().contains("first.toml"));
+        assert!(error.to_string().contains("second.yaml"));
+    }
+
+    #[test]
+    fn should_fail_when_nothing_matches() {
+        let root = directory("should-fail-when-nothing-matches", &[]);
+        let error = Config::from_paths([&root], None).unwrap_err();
+        assert!(matches!(error, LoadingError::NoFileFound { path } if path == root));
+        let error = Config::from_paths([Path::new("/nowhere/*.toml")], None).unwrap_err();
+        assert!(matches!(error, LoadingError::NoFileFound { .. }));
+    }
+}
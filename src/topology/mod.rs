@@ -1,17 +1,38 @@
 use std::collections::{HashMap, HashSet};
-use std::io::{Error, ErrorKind, Result as IOResult};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
-use crate::components::collector::Collector;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::components::buffer::{BufferConfig, ReceiverSlot};
+use crate::components::collector::{Collector, Taps};
 use crate::components::name::ComponentName;
-use crate::components::output::ComponentOutput;
-use crate::prelude::{create_channel, Receiver};
+use crate::components::output::{ComponentOutput, ComponentWithOutputs, NamedOutput};
+use crate::prelude::{Receiver, Sender};
 use crate::sinks::Sink;
 use crate::sources::Source;
 use crate::transforms::Transform;
 
+mod format;
+mod graph;
+mod interpolation;
+mod loader;
+mod reload;
+mod supervisor;
+mod testing;
 pub mod validation;
 
+pub use self::format::Format;
+use self::format::{Location, ParseError};
+pub use self::graph::GraphFormat;
+use self::interpolation::InterpolationError;
+pub use self::reload::ReloadError;
+use self::supervisor::RestartConfig;
+pub use self::testing::TestConfig;
+
 #[derive(Debug, thiserror::Error)]
 pub enum BuildError {
     #[error(transparent)]
@@ -24,11 +45,64 @@ pub enum BuildError {
     Validation(Vec<self::validation::ValidationError>),
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, thiserror::Error)]
+pub enum LoadingError {
+    #[error("unable to read configuration file {path:?}")]
+    UnableToRead {
+        path: PathBuf,
+        #[source]
+        cause: std::io::Error,
+    },
+    #[error("unable to interpolate configuration file {path:?}")]
+    UnableToInterpolate {
+        path: PathBuf,
+        #[source]
+        cause: InterpolationError,
+    },
+    #[error(
+        "unable to parse configuration file {path:?}{}",
+        .location.map(|location| format!(" at {location}")).unwrap_or_default()
+    )]
+    UnableToParse {
+        path: PathBuf,
+        location: Option<Location>,
+        #[source]
+        cause: ParseError,
+    },
+    #[error("invalid configuration path pattern {pattern:?}")]
+    InvalidPattern {
+        pattern: String,
+        #[source]
+        cause: glob::PatternError,
+    },
+    #[error("no configuration file found in {path:?}")]
+    NoFileFound { path: PathBuf },
+    #[error("the component {name} is defined in both {first:?} and {second:?}")]
+    DuplicatedComponent {
+        name: ComponentName,
+        first: PathBuf,
+        second: PathBuf,
+    },
+    #[error("the data directory is defined in both {first:?} and {second:?}")]
+    DuplicatedDataDir { first: PathBuf, second: PathBuf },
+}
+
+/// Reason why two configurations cannot be merged.
+#[derive(Debug, PartialEq, Eq)]
+enum MergeConflict {
+    Component(ComponentName),
+    DataDir,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 struct WithInputs<Inner> {
     #[serde(flatten)]
     inner: Inner,
     inputs: HashSet<ComponentOutput<'static>>,
+    #[serde(default)]
+    buffer: BufferConfig,
+    #[serde(default)]
+    restart: RestartConfig,
 }
 
 #[cfg(test)]
@@ -37,6 +111,8 @@ impl<Inner> WithInputs<Inner> {
         Self {
             inner: inner.into(),
             inputs: Default::default(),
+            buffer: Default::default(),
+            restart: Default::default(),
         }
     }
 
@@ -65,55 +141,213 @@ impl<Inner> WithInputs<Inner> {
     }
 }
 
-#[derive(Debug, Default, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+struct WithRestart<Inner> {
+    #[serde(flatten)]
+    inner: Inner,
+    #[serde(default)]
+    restart: RestartConfig,
+}
+
+impl<T: Into<crate::sources::Config>> From<T> for WithRestart<crate::sources::Config> {
+    fn from(value: T) -> Self {
+        Self {
+            inner: value.into(),
+            restart: Default::default(),
+        }
+    }
+}
+
+const DEFAULT_DATA_DIR: &str = "/var/lib/tiny-vector";
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
 pub struct Config {
-    sources: HashMap<ComponentName, crate::sources::Config>,
+    /// Directory where the components persist their state, like the disk buffers
+    #[serde(default)]
+    data_dir: Option<PathBuf>,
+    #[serde(default)]
+    sources: HashMap<ComponentName, WithRestart<crate::sources::Config>>,
+    #[serde(default)]
     transforms: HashMap<ComponentName, WithInputs<crate::transforms::Config>>,
+    #[serde(default)]
     sinks: HashMap<ComponentName, WithInputs<crate::sinks::Config>>,
+    /// Unit tests of the transforms, only used by the test command
+    #[serde(default)]
+    tests: Vec<TestConfig>,
 }
 
 impl Config {
-    pub fn from_path<P: AsRef<Path>>(path: P) -> IOResult<Self> {
-        let file = std::fs::read_to_string(path)?;
-        toml::de::from_str(&file).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+    /// Loads a configuration file, the format is guessed from its extension when not specified.
+    ///
+    /// The environment variables and secret files referenced in the file get interpolated
+    /// before parsing it.
+    pub fn from_path<P: AsRef<Path>>(
+        path: P,
+        format: Option<Format>,
+    ) -> Result<Self, LoadingError> {
+        let path = path.as_ref();
+        let file = std::fs::read_to_string(path).map_err(|cause| LoadingError::UnableToRead {
+            path: path.to_path_buf(),
+            cause,
+        })?;
+        let format = format.unwrap_or_else(|| Format::from_path(path));
+        let interpolated =
+            self::interpolation::interpolate(&file, format, |name| std::env::var(name).ok())
+                .map_err(|cause| LoadingError::UnableToInterpolate {
+                    path: path.to_path_buf(),
+                    cause,
+                })?;
+        format
+            .parse(&interpolated.content)
+            .map_err(|cause| LoadingError::UnableToParse {
+                path: path.to_path_buf(),
+                // pointing at the file as written, not at the interpolated values
+                location: cause
+                    .location(&interpolated.content)
+                    .map(|location| interpolated.original_location(&file, location)),
+                cause,
+            })
+    }
+
+    /// Lists every component with its kind.
+    pub fn components(&self) -> impl Iterator<Item = (&ComponentName, &'static str)> {
+        self.sources
+            .keys()
+            .map(|name| (name, crate::sources::COMPONENT_KIND))
+            .chain(
+                self.transforms
+                    .keys()
+                    .map(|name| (name, crate::transforms::COMPONENT_KIND)),
+            )
+            .chain(
+                self.sinks
+                    .keys()
+                    .map(|name| (name, crate::sinks::COMPONENT_KIND)),
+            )
+    }
+
+    /// Lists the edges of the graph, from an output to the component consuming it.
+    pub fn edges(&self) -> impl Iterator<Item = (&ComponentOutput<'static>, &ComponentName)> {
+        self.transforms
+            .iter()
+            .map(|(name, transform)| (name, &transform.inputs))
+            .chain(self.sinks.iter().map(|(name, sink)| (name, &sink.inputs)))
+            .flat_map(|(name, inputs)| inputs.iter().map(move |input| (input, name)))
+    }
+
+    pub fn tests(&self) -> &[TestConfig] {
+        &self.tests
+    }
+
+    /// Checks that the component exists and produces the given output.
+    pub fn has_output(&self, name: &str, output: &NamedOutput) -> bool {
+        if let Some(source) = self.sources.get(name) {
+            return source.inner.has_output(output);
+        }
+        self.transforms
+            .get(name)
+            .is_some_and(|transform| transform.inner.has_output(output))
+    }
+
+    fn contains(&self, name: &ComponentName) -> bool {
+        self.sources.contains_key(name)
+            || self.transforms.contains_key(name)
+            || self.sinks.contains_key(name)
+    }
+
+    fn merge(&mut self, other: Config) -> Result<(), MergeConflict> {
+        let Config {
+            data_dir,
+            sources,
+            transforms,
+            sinks,
+            tests,
+        } = other;
+        if self.data_dir.is_some() && data_dir.is_some() {
+            return Err(MergeConflict::DataDir);
+        }
+        if let Some(name) = sources
+            .keys()
+            .chain(transforms.keys())
+            .chain(sinks.keys())
+            .find(|name| self.contains(name))
+        {
+            return Err(MergeConflict::Component(name.clone()));
+        }
+        self.data_dir = self.data_dir.take().or(data_dir);
+        self.sources.extend(sources);
+        self.transforms.extend(transforms);
+        self.sinks.extend(sinks);
+        self.tests.extend(tests);
+        Ok(())
     }
 
     async fn compile(self) -> Result<Topology, BuildError> {
+        let config = self.clone();
         let mut sources = HashMap::with_capacity(self.sources.len());
         let mut transforms = HashMap::with_capacity(self.transforms.len());
         let mut sinks = HashMap::with_capacity(self.sinks.len());
 
-        for (name, WithInputs { inner, inputs }) in self.sinks.into_iter() {
+        for (
+            name,
+            WithInputs {
+                inner,
+                inputs,
+                buffer,
+                restart,
+            },
+        ) in self.sinks.into_iter()
+        {
             sinks.insert(
                 name,
                 WithInputs {
                     inner: inner.build().await?,
                     inputs,
+                    buffer,
+                    restart,
                 },
             );
         }
 
-        for (name, WithInputs { inner, inputs }) in self.transforms.into_iter() {
+        for (
+            name,
+            WithInputs {
+                inner,
+                inputs,
+                buffer,
+                restart,
+            },
+        ) in self.transforms.into_iter()
+        {
             transforms.insert(
                 name,
                 WithInputs {
                     inner: inner.build()?,
                     inputs,
+                    buffer,
+                    restart,
                 },
             );
         }
 
         for (name, inner) in self.sources.into_iter() {
-            sources.insert(name, inner.build()?);
+            sources.insert(name, inner.inner.build()?);
         }
 
         Ok(Topology {
+            config,
             sources,
             transforms,
             sinks,
         })
     }
 
+    fn data_dir(&self) -> &Path {
+        self.data_dir
+            .as_deref()
+            .unwrap_or_else(|| Path::new(DEFAULT_DATA_DIR))
+    }
+
     pub async fn build(self) -> Result<Topology, BuildError> {
         let c = self.validate().map_err(BuildError::Validation)?;
         c.compile().await
@@ -128,91 +362,380 @@ pub enum StartingError {
     Transform(#[from] crate::transforms::StartingError),
     #[error(transparent)]
     Sink(#[from] crate::sinks::StartingError),
+    #[error("unable to create the buffer of {name}")]
+    Buffer {
+        name: ComponentName,
+        #[source]
+        cause: std::io::Error,
+    },
 }
 
-#[cfg_attr(test, derive(Default))]
 pub struct Topology {
+    config: Config,
     sources: HashMap<ComponentName, Source>,
     transforms: HashMap<ComponentName, WithInputs<Transform>>,
     sinks: HashMap<ComponentName, WithInputs<Sink>>,
 }
 
 impl Topology {
-    fn prepare_wiring(
+    async fn create_channels(
         &self,
-    ) -> (
-        HashMap<ComponentName, Collector>,
-        HashMap<ComponentName, Receiver>,
-    ) {
-        let mut receivers = HashMap::new();
-        let mut collectors = HashMap::<ComponentName, Collector>::new();
-        for (name, sink) in self.sinks.iter() {
-            let (sender, receiver) = create_channel(1000);
-            receivers.insert(name.clone(), receiver);
-            for input in sink.inputs.iter() {
-                let collector = collectors.entry(input.to_owned_name()).or_default();
-                collector.add_output(input.to_owned_output(), sender.clone());
-            }
-        }
-        for (name, transform) in self.transforms.iter() {
-            let (sender, receiver) = create_channel(1000);
+    ) -> Result<
+        (
+            HashMap<ComponentName, Sender>,
+            HashMap<ComponentName, Receiver>,
+        ),
+        StartingError,
+    > {
+        let buffers = self.config.data_dir().join("buffers");
+        let mut senders = HashMap::with_capacity(self.transforms.len() + self.sinks.len());
+        let mut receivers = HashMap::with_capacity(self.transforms.len() + self.sinks.len());
+        let configs = self
+            .transforms
+            .iter()
+            .map(|(name, transform)| (name, &transform.buffer))
+            .chain(self.sinks.iter().map(|(name, sink)| (name, &sink.buffer)));
+        for (name, buffer) in configs {
+            let (sender, receiver) =
+                buffer
+                    .build(&buffers.join(name.as_ref()))
+                    .await
+                    .map_err(|cause| StartingError::Buffer {
+                        name: name.clone(),
+                        cause,
+                    })?;
+            senders.insert(name.clone(), sender);
             receivers.insert(name.clone(), receiver);
-            for input in transform.inputs.iter() {
-                let collector = collectors.entry(input.to_owned_name()).or_default();
-                collector.add_output(input.to_owned_output(), sender.clone());
-            }
         }
-        (collectors, receivers)
+        Ok((senders, receivers))
     }
 
     pub(crate) async fn start(self) -> Result<Instance, StartingError> {
-        let (mut collectors, mut receivers) = self.prepare_wiring();
+        let (senders, receivers) = self.create_channels().await?;
+        let collectors = wire(&self.config, &senders);
+
+        let mut instance = Instance {
+            config: Config::default(),
+            shutdown: CancellationToken::new(),
+            senders,
+            sources: HashMap::with_capacity(self.sources.len()),
+            transforms: HashMap::with_capacity(self.transforms.len()),
+            sinks: HashMap::with_capacity(self.sinks.len()),
+        };
+        instance
+            .start_components(self, receivers, collectors)
+            .await?;
+        Ok(instance)
+    }
+}
 
-        let mut sources = HashMap::with_capacity(self.sources.len());
-        let mut transforms = HashMap::with_capacity(self.transforms.len());
-        let mut sinks = HashMap::with_capacity(self.sinks.len());
+/// Builds the collector of every component by plugging the senders of the components
+/// consuming its outputs. Consumers without sender are ignored.
+fn wire(
+    config: &Config,
+    senders: &HashMap<ComponentName, Sender>,
+) -> HashMap<ComponentName, Collector> {
+    let consumers = config
+        .transforms
+        .iter()
+        .map(|(name, transform)| (name, &transform.inputs))
+        .chain(config.sinks.iter().map(|(name, sink)| (name, &sink.inputs)));
+    let mut collectors = HashMap::<ComponentName, Collector>::new();
+    for (name, inputs) in consumers {
+        let Some(sender) = senders.get(name) else {
+            continue;
+        };
+        for input in inputs.iter() {
+            let collector = collectors.entry(input.to_owned_name()).or_default();
+            collector.add_output(input.to_owned_output(), sender.clone());
+        }
+    }
+    collectors
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ShutdownError {
+    #[error("the components didn't stop within {0:?}")]
+    Timeout(Duration),
+}
+
+async fn wait_for_components(kind: &str, handlers: HashMap<ComponentName, JoinHandle<()>>) {
+    for (name, handler) in handlers {
+        if let Err(err) = handler.await {
+            tracing::error!("something went wrong while waiting for {kind} {name}: {err:?}");
+        }
+    }
+}
+
+struct RunningSource {
+    collector: Collector,
+    shutdown: CancellationToken,
+    handler: JoinHandle<()>,
+    running: Arc<AtomicBool>,
+}
+
+struct RunningTransform {
+    collector: Collector,
+    /// Tells the supervisor not to restart the transform once it stops
+    stop: CancellationToken,
+    handler: JoinHandle<()>,
+    running: Arc<AtomicBool>,
+}
+
+struct RunningSink {
+    /// Tells the supervisor not to restart the sink once it stops
+    stop: CancellationToken,
+    handler: JoinHandle<()>,
+    running: Arc<AtomicBool>,
+}
+
+pub(crate) struct Instance {
+    /// Configuration of the running components
+    config: Config,
+    shutdown: CancellationToken,
+    /// Senders plugged into the receivers of the transforms and the sinks
+    senders: HashMap<ComponentName, Sender>,
+    sources: HashMap<ComponentName, RunningSource>,
+    transforms: HashMap<ComponentName, RunningTransform>,
+    sinks: HashMap<ComponentName, RunningSink>,
+}
+
+/// Handle on a running component.
+#[derive(Clone, Debug)]
+pub struct ComponentHandle {
+    pub kind: &'static str,
+    /// Updated by the supervisor, false while the component waits to be restarted
+    running: Arc<AtomicBool>,
+    /// Copies of the events leaving the component, only for the components with outputs
+    pub taps: Option<Arc<Taps>>,
+}
+
+impl ComponentHandle {
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+}
+
+impl Instance {
+    /// Configuration of the running components.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
 
-        for (name, sink) in self.sinks.into_iter() {
+    pub fn components(&self) -> HashMap<ComponentName, ComponentHandle> {
+        let handle =
+            |kind: &'static str, running: &Arc<AtomicBool>, collector: Option<&Collector>| {
+                ComponentHandle {
+                    kind,
+                    running: running.clone(),
+                    taps: collector.map(Collector::taps),
+                }
+            };
+        self.sources
+            .iter()
+            .map(|(name, item)| {
+                let kind = crate::sources::COMPONENT_KIND;
+                (
+                    name.clone(),
+                    handle(kind, &item.running, Some(&item.collector)),
+                )
+            })
+            .chain(self.transforms.iter().map(|(name, item)| {
+                let kind = crate::transforms::COMPONENT_KIND;
+                (
+                    name.clone(),
+                    handle(kind, &item.running, Some(&item.collector)),
+                )
+            }))
+            .chain(self.sinks.iter().map(|(name, item)| {
+                let kind = crate::sinks::COMPONENT_KIND;
+                (name.clone(), handle(kind, &item.running, None))
+            }))
+            .collect()
+    }
+
+    async fn start_components(
+        &mut self,
+        topology: Topology,
+        mut receivers: HashMap<ComponentName, Receiver>,
+        mut collectors: HashMap<ComponentName, Collector>,
+    ) -> Result<(), StartingError> {
+        let Topology {
+            config,
+            sources,
+            transforms,
+            sinks,
+        } = topology;
+
+        for (name, sink) in sinks.into_iter() {
+            let config = config.sinks.get(&name).expect("config for sink");
             let receiver = receivers.remove(&name).expect("receiver for sink");
-            let handler = sink.inner.start(&name, receiver).await?;
-            sinks.insert(name, handler);
+            let slot = ReceiverSlot::default();
+            let handler = sink.inner.start(&name, receiver.attach(&slot)).await?;
+            let stop = self.shutdown.child_token();
+            let running = Arc::new(AtomicBool::new(true));
+            let handler = supervisor::supervise(
+                name.clone(),
+                crate::sinks::COMPONENT_KIND,
+                config.restart.clone(),
+                stop.clone(),
+                handler,
+                running.clone(),
+                supervisor::RestartableSink {
+                    name: name.clone(),
+                    config: config.inner.clone(),
+                    receiver: slot,
+                },
+            );
+            self.config.sinks.insert(name.clone(), config.clone());
+            self.sinks.insert(
+                name,
+                RunningSink {
+                    stop,
+                    handler,
+                    running,
+                },
+            );
         }
-        for (name, transform) in self.transforms.into_iter() {
+        for (name, transform) in transforms.into_iter() {
+            let config = config.transforms.get(&name).expect("config for transform");
             let receiver = receivers.remove(&name).expect("receiver for transform");
             let collector = collectors.remove(&name).unwrap_or_default();
-            let handler = transform.inner.start(&name, receiver, collector).await?;
-            transforms.insert(name, handler);
+            let slot = ReceiverSlot::default();
+            let handler = transform
+                .inner
+                .start(&name, receiver.attach(&slot), collector.clone())
+                .await?;
+            let stop = self.shutdown.child_token();
+            let running = Arc::new(AtomicBool::new(true));
+            let handler = supervisor::supervise(
+                name.clone(),
+                crate::transforms::COMPONENT_KIND,
+                config.restart.clone(),
+                stop.clone(),
+                handler,
+                running.clone(),
+                supervisor::RestartableTransform {
+                    name: name.clone(),
+                    config: config.inner.clone(),
+                    collector: collector.clone(),
+                    receiver: slot,
+                },
+            );
+            self.config.transforms.insert(name.clone(), config.clone());
+            self.transforms.insert(
+                name,
+                RunningTransform {
+                    collector,
+                    stop,
+                    handler,
+                    running,
+                },
+            );
         }
-        for (name, source) in self.sources.into_iter() {
+        for (name, source) in sources.into_iter() {
+            let config = config.sources.get(&name).expect("config for source");
             let collector = collectors.remove(&name).unwrap_or_default();
-            let handler = source.start(&name, collector).await?;
-            sources.insert(name, handler);
+            let shutdown = self.shutdown.child_token();
+            let handler = source
+                .start(&name, collector.clone(), shutdown.clone())
+                .await?;
+            let running = Arc::new(AtomicBool::new(true));
+            let handler = supervisor::supervise(
+                name.clone(),
+                crate::sources::COMPONENT_KIND,
+                config.restart.clone(),
+                shutdown.clone(),
+                handler,
+                running.clone(),
+                supervisor::RestartableSource {
+                    name: name.clone(),
+                    config: config.inner.clone(),
+                    collector: collector.clone(),
+                    shutdown: shutdown.clone(),
+                },
+            );
+            self.config.sources.insert(name.clone(), config.clone());
+            self.sources.insert(
+                name,
+                RunningSource {
+                    collector,
+                    shutdown,
+                    handler,
+                    running,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Replaces the outputs of every running component with the ones defined by the config.
+    fn rewire(&self, config: &Config) {
+        let collectors = wire(config, &self.senders);
+        let empty = Collector::default();
+        for (name, source) in self.sources.iter() {
+            source
+                .collector
+                .replace_with(collectors.get(name).unwrap_or(&empty));
         }
+        for (name, transform) in self.transforms.iter() {
+            transform
+                .collector
+                .replace_with(collectors.get(name).unwrap_or(&empty));
+        }
+    }
 
-        Ok(Instance {
+    /// Stops the sources and waits for the remaining events to go through the transforms
+    /// and the sinks. Components still running after the timeout get aborted.
+    pub async fn shutdown(self, timeout: Duration) -> Result<(), ShutdownError> {
+        let Instance {
+            config: _,
+            shutdown,
+            senders,
             sources,
             transforms,
             sinks,
-        })
-    }
-}
-
-pub(crate) struct Instance {
-    sources: HashMap<ComponentName, tokio::task::JoinHandle<()>>,
-    transforms: HashMap<ComponentName, tokio::task::JoinHandle<()>>,
-    sinks: HashMap<ComponentName, tokio::task::JoinHandle<()>>,
-}
-
-impl Instance {
-    pub async fn wait(self) {
-        for (name, handler) in self
-            .sources
+        } = self;
+        tracing::info!("stopping sources");
+        // also prevents the supervisors from restarting the components
+        shutdown.cancel();
+        // the channels get closed once all the senders are dropped
+        drop(senders);
+        let sources: HashMap<_, _> = sources
             .into_iter()
-            .chain(self.transforms.into_iter())
-            .chain(self.sinks.into_iter())
-        {
-            if let Err(err) = handler.await {
-                eprintln!("something went wront while waiting for {name:?}: {err:?}");
+            .map(|(name, item)| (name, item.handler))
+            .collect();
+        let transforms: HashMap<_, _> = transforms
+            .into_iter()
+            .map(|(name, item)| (name, item.handler))
+            .collect();
+        let sinks: HashMap<_, _> = sinks
+            .into_iter()
+            .map(|(name, item)| (name, item.handler))
+            .collect();
+
+        let aborts = sources
+            .values()
+            .chain(transforms.values())
+            .chain(sinks.values())
+            .map(|handler| handler.abort_handle())
+            .collect::<Vec<_>>();
+
+        let draining = async move {
+            wait_for_components(crate::sources::COMPONENT_KIND, sources).await;
+            tracing::info!("sources stopped, draining transforms");
+            wait_for_components(crate::transforms::COMPONENT_KIND, transforms).await;
+            tracing::info!("transforms stopped, draining sinks");
+            wait_for_components(crate::sinks::COMPONENT_KIND, sinks).await;
+        };
+        match tokio::time::timeout(timeout, draining).await {
+            Ok(_) => {
+                tracing::info!("all components stopped");
+                Ok(())
+            }
+            Err(_) => {
+                aborts.iter().for_each(|handler| handler.abort());
+                Err(ShutdownError::Timeout(timeout))
             }
         }
     }
@@ -220,13 +743,119 @@ impl Instance {
 
 #[cfg(test)]
 mod tests {
-    use super::Config;
+    use super::format::Location;
+    use super::{Config, LoadingError, MergeConflict};
     use crate::components::name::ComponentName;
 
-    async fn run_config(config: Config) {
-        let _topology = config.build().await.unwrap().start().await;
+    #[test]
+    fn should_merge_configs() {
+        let mut root = Config::default();
+        root.sources.insert(
+            ComponentName::new("generator"),
+            crate::sources::random_logs::Config::default().into(),
+        );
+        let mut other = Config::default();
+        other.sinks.insert(
+            ComponentName::new("output"),
+            crate::topology::WithInputs::new(crate::sinks::black_hole::Config::default())
+                .with_default_input("generator"),
+        );
+        root.merge(other).unwrap();
+        assert!(root.validate().is_ok());
+    }
+
+    #[test]
+    fn should_not_merge_configs_with_duplicated_names() {
+        let mut root = Config::default();
+        root.sources.insert(
+            ComponentName::new("generator"),
+            crate::sources::random_logs::Config::default().into(),
+        );
+        let mut other = Config::default();
+        other.sinks.insert(
+            ComponentName::new("generator"),
+            crate::topology::WithInputs::new(crate::sinks::black_hole::Config::default())
+                .with_default_input("generator"),
+        );
+        let error = root.merge(other).unwrap_err();
+        assert_eq!(
+            error,
+            MergeConflict::Component(ComponentName::new("generator"))
+        );
+    }
+
+    #[test]
+    fn should_fail_loading_with_missing_variable() {
+        let path = std::env::temp_dir().join("tiny-vector-should-fail-with-missing-variable.toml");
+        std::fs::write(
+            &path,
+            "[sources.generator]\ntype = \"random_logs\"\ninterval = ${TINY_VECTOR_UNDEFINED_INTERVAL}\n",
+        )
+        .unwrap();
+        let error = Config::from_path(&path, None).unwrap_err();
+        let cause = std::error::Error::source(&error).unwrap().to_string();
+        assert!(cause.contains("TINY_VECTOR_UNDEFINED_INTERVAL"), "{cause}");
+    }
+
+    #[test]
+    fn should_locate_errors_in_original_file() {
+        let path = std::env::temp_dir().join("tiny-vector-should-locate-errors-in-original.toml");
+        let content = "[sources.generator]\ntype = \"random_logs\"\nlabels = { a = \"${TINY_VECTOR_UNDEFINED_LABEL:-x}\", b = fast }\n";
+        std::fs::write(&path, content).unwrap();
+        let error = Config::from_path(&path, None).unwrap_err();
+        let LoadingError::UnableToParse { location, .. } = error else {
+            panic!("unexpected error {error:?}");
+        };
+        let line = content.lines().nth(2).unwrap();
+        assert_eq!(
+            location,
+            Some(Location {
+                line: 3,
+                column: line.find("fast").unwrap() + 1
+            })
+        );
+    }
+
+    #[test]
+    fn should_parse_restart_policy() {
+        use super::supervisor::{RestartConfig, RestartPolicy};
+
+        let config: Config = toml::from_str(
+            r#"
+[sources.generator]
+type = "random_logs"
+restart = { policy = "always", initial_backoff = 100 }
+
+[sinks.output]
+type = "black_hole"
+inputs = ["generator"]
+"#,
+        )
+        .unwrap();
+        let source = config.sources.get("generator").unwrap();
+        assert_eq!(
+            source.restart,
+            RestartConfig {
+                policy: RestartPolicy::Always,
+                initial_backoff: 100,
+                ..Default::default()
+            }
+        );
+        let sink = config.sinks.get("output").unwrap();
+        assert_eq!(sink.restart, RestartConfig::default());
+    }
+
+    /// The sink name must be unique among the tests, given the metrics are shared.
+    async fn run_config(config: Config, sink: &str) {
+        let instance = config.build().await.unwrap().start().await.unwrap();
+        // keeps the counters of the sink once it's stopped
+        let metrics = crate::components::metrics::find(sink).unwrap();
         tokio::time::sleep(tokio::time::Duration::new(1, 0)).await;
-        // TODO make sure event came through
+        instance
+            .shutdown(tokio::time::Duration::new(1, 0))
+            .await
+            .unwrap();
+        assert!(metrics.events_in.load(std::sync::atomic::Ordering::Relaxed) > 0);
     }
 
     #[tokio::test]
@@ -247,11 +876,11 @@ mod tests {
                 .with_named_input("first", "dropped"),
         );
         root.sinks.insert(
-            ComponentName::new("output"),
+            ComponentName::new("simple-output"),
             crate::topology::WithInputs::new(crate::sinks::black_hole::Config::default())
                 .with_named_input("second", "dropped"),
         );
-        run_config(root).await;
+        run_config(root, "simple-output").await;
     }
 
     #[tokio::test]
@@ -272,10 +901,39 @@ mod tests {
                 .with_default_input("generator"),
         );
         root.sinks.insert(
-            ComponentName::new("output"),
+            ComponentName::new("weird-output"),
             crate::topology::WithInputs::new(crate::sinks::black_hole::Config::default())
                 .with_named_input("second", "dropped"),
         );
-        run_config(root).await;
+        run_config(root, "weird-output").await;
+    }
+
+    #[cfg(feature = "sink-file")]
+    #[tokio::test]
+    async fn should_drain_events_on_shutdown() {
+        let path = std::env::temp_dir().join("tiny-vector-should-drain-events-on-shutdown.log");
+        let _ = std::fs::remove_file(&path);
+        let config: Config = toml::from_str(&format!(
+            r#"
+[sources.generator]
+type = "random_logs"
+interval = 10
+
+[sinks.output]
+type = "file"
+path = {path:?}
+inputs = ["generator"]
+"#
+        ))
+        .unwrap();
+        let instance = config.build().await.unwrap().start().await.unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        instance
+            .shutdown(tokio::time::Duration::new(1, 0))
+            .await
+            .unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.lines().count() > 0);
+        assert!(content.ends_with('\n'));
     }
 }
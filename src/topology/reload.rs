@@ -0,0 +1,337 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+use super::{wire, BuildError, Config, Instance, StartingError};
+use crate::components::buffer::{BufferConfig, BufferType};
+use crate::components::name::ComponentName;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReloadError {
+    #[error(transparent)]
+    Build(#[from] BuildError),
+    #[error(transparent)]
+    Starting(#[from] StartingError),
+}
+
+/// Components that need to be stopped or started to go from one config to another.
+///
+/// A component that only changed its inputs is neither stopped nor started, it just
+/// gets rewired. Changing the buffer requires a restart since it recreates the channel,
+/// changing the restart policy requires a restart since it's held by the supervisor.
+/// Changing the data directory restarts the components with a disk buffer, so that they
+/// don't keep writing in the previous one.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Diff {
+    /// Components that have been removed or changed
+    to_stop: HashSet<ComponentName>,
+    /// Components that have been added or changed
+    to_start: HashSet<ComponentName>,
+}
+
+impl Diff {
+    fn new(current: &Config, next: &Config) -> Self {
+        let mut diff = Self::default();
+        let moved = current.data_dir() != next.data_dir();
+        let same_buffer =
+            |a: &BufferConfig, b: &BufferConfig| a == b && !(moved && a.kind == BufferType::Disk);
+        diff.compare(&current.sources, &next.sources, |a, b| a == b);
+        diff.compare(&current.transforms, &next.transforms, |a, b| {
+            a.inner == b.inner && same_buffer(&a.buffer, &b.buffer) && a.restart == b.restart
+        });
+        diff.compare(&current.sinks, &next.sinks, |a, b| {
+            a.inner == b.inner && same_buffer(&a.buffer, &b.buffer) && a.restart == b.restart
+        });
+        diff
+    }
+
+    fn compare<T, F>(
+        &mut self,
+        current: &HashMap<ComponentName, T>,
+        next: &HashMap<ComponentName, T>,
+        same: F,
+    ) where
+        F: Fn(&T, &T) -> bool,
+    {
+        for (name, value) in current.iter() {
+            match next.get(name) {
+                Some(other) if same(value, other) => {}
+                Some(_) => {
+                    self.to_stop.insert(name.clone());
+                    self.to_start.insert(name.clone());
+                }
+                None => {
+                    self.to_stop.insert(name.clone());
+                }
+            }
+        }
+        for name in next.keys() {
+            if !current.contains_key(name) {
+                self.to_start.insert(name.clone());
+            }
+        }
+    }
+}
+
+impl Config {
+    fn subset<F: Fn(&ComponentName) -> bool>(&self, filter: F) -> Config {
+        Config {
+            data_dir: self.data_dir.clone(),
+            sources: self
+                .sources
+                .iter()
+                .filter(|(name, _)| filter(name))
+                .map(|(name, item)| (name.clone(), item.clone()))
+                .collect(),
+            transforms: self
+                .transforms
+                .iter()
+                .filter(|(name, _)| filter(name))
+                .map(|(name, item)| (name.clone(), item.clone()))
+                .collect(),
+            sinks: self
+                .sinks
+                .iter()
+                .filter(|(name, _)| filter(name))
+                .map(|(name, item)| (name.clone(), item.clone()))
+                .collect(),
+            tests: Vec::new(),
+        }
+    }
+}
+
+impl Instance {
+    /// Removes the component from the running ones, the returned handler finishes once
+    /// the component has handled its remaining events.
+    fn detach(&mut self, name: &ComponentName) -> Option<JoinHandle<()>> {
+        self.senders.remove(name);
+        if let Some(source) = self.sources.remove(name) {
+            source.shutdown.cancel();
+            return Some(source.handler);
+        }
+        if let Some(transform) = self.transforms.remove(name) {
+            transform.stop.cancel();
+            return Some(transform.handler);
+        }
+        let sink = self.sinks.remove(name)?;
+        sink.stop.cancel();
+        Some(sink.handler)
+    }
+
+    /// Applies the new config to the running topology.
+    ///
+    /// Only the components that have been added, removed or changed are started or stopped,
+    /// the others keep running and get their outputs replaced. If one of the new components
+    /// cannot be built, the running topology is left untouched.
+    pub async fn reload(&mut self, next: Config, timeout: Duration) -> Result<(), ReloadError> {
+        let next = next.validate().map_err(BuildError::Validation)?;
+        let diff = Diff::new(&self.config, &next);
+        let topology = next
+            .subset(|name| diff.to_start.contains(name))
+            .compile()
+            .await?;
+
+        let mut stopping = Vec::with_capacity(diff.to_stop.len());
+        for name in diff.to_stop.iter() {
+            tracing::info!("stopping component {name}");
+            if let Some(handler) = self.detach(name) {
+                stopping.push((name, handler));
+            }
+        }
+        self.config = next.subset(|name| !diff.to_start.contains(name));
+        // unplug the stopped components so that they can drain their channels
+        self.rewire(&next);
+        for (name, handler) in stopping {
+            let abort = handler.abort_handle();
+            match tokio::time::timeout(timeout, handler).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(err)) => {
+                    tracing::error!("something went wrong while stopping {name}: {err:?}")
+                }
+                Err(_) => {
+                    tracing::warn!("component {name} didn't stop within {timeout:?}, aborting");
+                    abort.abort();
+                }
+            }
+        }
+
+        let (senders, receivers) = match topology.create_channels().await {
+            Ok(inner) => inner,
+            Err(err) => {
+                self.rewire(&self.config);
+                return Err(err.into());
+            }
+        };
+        self.senders.extend(senders);
+        let collectors = wire(&next, &self.senders);
+        // the new components get their outputs directly from the collectors
+        // while the other ones get rewired
+        for (name, source) in self.sources.iter() {
+            if let Some(collector) = collectors.get(name) {
+                source.collector.replace_with(collector);
+            }
+        }
+        for (name, transform) in self.transforms.iter() {
+            if let Some(collector) = collectors.get(name) {
+                transform.collector.replace_with(collector);
+            }
+        }
+        for name in diff.to_start.iter() {
+            tracing::info!("starting component {name}");
+        }
+        let result = self.start_components(topology, receivers, collectors).await;
+        if result.is_err() {
+            // forget about the components that couldn't start
+            self.senders.retain(|name, _| {
+                self.transforms.contains_key(name) || self.sinks.contains_key(name)
+            });
+            self.rewire(&self.config);
+        }
+        Ok(result?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::Diff;
+    use crate::components::buffer::BufferType;
+    use crate::components::name::ComponentName;
+    use crate::topology::{Config, WithInputs};
+
+    fn base() -> Config {
+        let mut config = Config::default();
+        config.sources.insert(
+            ComponentName::new("generator"),
+            crate::sources::random_logs::Config::default().into(),
+        );
+        config.transforms.insert(
+            ComponentName::new("router"),
+            WithInputs::new(crate::transforms::route::Config::default())
+                .with_default_input("generator"),
+        );
+        config.sinks.insert(
+            ComponentName::new("output"),
+            WithInputs::new(crate::sinks::black_hole::Config::default())
+                .with_named_input("router", "dropped"),
+        );
+        config
+    }
+
+    #[test]
+    fn should_not_change_anything_with_the_same_config() {
+        assert_eq!(Diff::new(&base(), &base()), Diff::default());
+    }
+
+    #[test]
+    fn should_only_rewire_when_inputs_change() {
+        let mut next = base();
+        next.sinks.insert(
+            ComponentName::new("output"),
+            WithInputs::new(crate::sinks::black_hole::Config::default())
+                .with_default_input("generator"),
+        );
+        assert_eq!(Diff::new(&base(), &next), Diff::default());
+    }
+
+    #[test]
+    fn should_restart_when_buffer_changes() {
+        let mut next = base();
+        if let Some(sink) = next.sinks.get_mut(&ComponentName::new("output")) {
+            sink.buffer.capacity = 10;
+        }
+        let expected = HashSet::from_iter([ComponentName::new("output")]);
+        assert_eq!(
+            Diff::new(&base(), &next),
+            Diff {
+                to_stop: expected.clone(),
+                to_start: expected,
+            }
+        );
+    }
+
+    #[test]
+    fn should_restart_disk_buffers_when_data_dir_changes() {
+        let mut current = base();
+        if let Some(sink) = current.sinks.get_mut(&ComponentName::new("output")) {
+            sink.buffer.kind = BufferType::Disk;
+        }
+        let mut next = current.clone();
+        next.data_dir = Some("/tmp/elsewhere".into());
+        let expected = HashSet::from_iter([ComponentName::new("output")]);
+        assert_eq!(
+            Diff::new(&current, &next),
+            Diff {
+                to_stop: expected.clone(),
+                to_start: expected,
+            }
+        );
+    }
+
+    #[test]
+    fn should_restart_changed_components() {
+        let mut next = base();
+        next.sources.insert(
+            ComponentName::new("generator"),
+            crate::sources::random_logs::Config { interval: Some(10) }.into(),
+        );
+        next.sinks.remove(&ComponentName::new("output"));
+        next.sinks.insert(
+            ComponentName::new("console"),
+            WithInputs::new(crate::sinks::console::Config::default())
+                .with_named_input("router", "dropped"),
+        );
+        assert_eq!(
+            Diff::new(&base(), &next),
+            Diff {
+                to_stop: HashSet::from_iter([
+                    ComponentName::new("generator"),
+                    ComponentName::new("output")
+                ]),
+                to_start: HashSet::from_iter([
+                    ComponentName::new("generator"),
+                    ComponentName::new("console")
+                ]),
+            }
+        );
+    }
+
+    #[cfg(feature = "sink-file")]
+    #[tokio::test]
+    async fn should_reload_running_topology() {
+        let first = std::env::temp_dir().join("tiny-vector-should-reload-first.log");
+        let second = std::env::temp_dir().join("tiny-vector-should-reload-second.log");
+        let _ = std::fs::remove_file(&first);
+        let _ = std::fs::remove_file(&second);
+        let config = |path: &std::path::Path| -> Config {
+            toml::from_str(&format!(
+                r#"
+[sources.generator]
+type = "random_logs"
+interval = 10
+
+[sinks.output]
+type = "file"
+path = {path:?}
+inputs = ["generator"]
+"#
+            ))
+            .unwrap()
+        };
+        let timeout = tokio::time::Duration::new(1, 0);
+        let mut instance = config(&first).build().await.unwrap().start().await.unwrap();
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        instance.reload(config(&second), timeout).await.unwrap();
+        let first_count = std::fs::read_to_string(&first).unwrap().lines().count();
+        assert!(first_count > 0);
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        instance.shutdown(timeout).await.unwrap();
+        // the previous sink has been stopped and the source kept sending to the new one
+        let content = std::fs::read_to_string(&first).unwrap();
+        assert_eq!(content.lines().count(), first_count);
+        let content = std::fs::read_to_string(&second).unwrap();
+        assert!(content.lines().count() > 0);
+    }
+}
@@ -0,0 +1,404 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use super::{BuildError, StartingError};
+use crate::components::buffer::ReceiverSlot;
+use crate::components::collector::Collector;
+use crate::components::name::ComponentName;
+use crate::components::ExecutionError;
+
+type Handler = JoinHandle<Result<(), ExecutionError>>;
+
+const DEFAULT_INITIAL_BACKOFF: u64 = 1000;
+const DEFAULT_MAX_BACKOFF: u64 = 60_000;
+
+fn default_initial_backoff() -> u64 {
+    DEFAULT_INITIAL_BACKOFF
+}
+
+fn default_max_backoff() -> u64 {
+    DEFAULT_MAX_BACKOFF
+}
+
+/// When a component that stopped on its own should be started again.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartPolicy {
+    /// The component stays stopped
+    Never,
+    /// The component gets restarted whenever it stops before being asked to, even when it
+    /// returned normally, like a sink whose inputs are gone
+    Always,
+    /// The component gets restarted when it panics or returns an error, like a source
+    /// leaving its loop on a send error
+    #[default]
+    OnFailure,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize)]
+pub struct RestartConfig {
+    #[serde(default)]
+    pub policy: RestartPolicy,
+    /// Delay before restarting the component, in ms, doubled after each consecutive restart
+    #[serde(default = "default_initial_backoff")]
+    pub initial_backoff: u64,
+    /// Maximum delay between two restarts, in ms. A component running for longer than
+    /// this delay gets its backoff reset.
+    #[serde(default = "default_max_backoff")]
+    pub max_backoff: u64,
+}
+
+impl Default for RestartConfig {
+    fn default() -> Self {
+        Self {
+            policy: RestartPolicy::default(),
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+        }
+    }
+}
+
+impl RestartConfig {
+    /// Called when the component stopped without being asked to.
+    fn should_restart(&self, failed: bool) -> bool {
+        match self.policy {
+            RestartPolicy::Never => false,
+            RestartPolicy::Always => true,
+            RestartPolicy::OnFailure => failed,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RestartError {
+    #[error(transparent)]
+    Build(#[from] BuildError),
+    #[error(transparent)]
+    Starting(#[from] StartingError),
+    #[error("the receiver of the component is still in use")]
+    ReceiverInUse,
+}
+
+/// Builds a component from its config and starts it again.
+pub(super) trait Restartable: Send + 'static {
+    fn restart(&mut self) -> impl Future<Output = Result<Handler, RestartError>> + Send;
+
+    /// Called when the component will not be restarted.
+    fn abandon(&mut self) {}
+}
+
+pub(super) struct RestartableSource {
+    pub name: ComponentName,
+    pub config: crate::sources::Config,
+    pub collector: Collector,
+    pub shutdown: CancellationToken,
+}
+
+impl Restartable for RestartableSource {
+    async fn restart(&mut self) -> Result<Handler, RestartError> {
+        let source = self.config.clone().build().map_err(BuildError::from)?;
+        Ok(source
+            .start(&self.name, self.collector.clone(), self.shutdown.clone())
+            .await
+            .map_err(StartingError::from)?)
+    }
+}
+
+pub(super) struct RestartableTransform {
+    pub name: ComponentName,
+    pub config: crate::transforms::Config,
+    pub collector: Collector,
+    pub receiver: ReceiverSlot,
+}
+
+impl Restartable for RestartableTransform {
+    async fn restart(&mut self) -> Result<Handler, RestartError> {
+        let transform = self.config.clone().build().map_err(BuildError::from)?;
+        let receiver = self.receiver.take().ok_or(RestartError::ReceiverInUse)?;
+        Ok(transform
+            .start(&self.name, receiver, self.collector.clone())
+            .await
+            .map_err(StartingError::from)?)
+    }
+
+    fn abandon(&mut self) {
+        // the upstream components get an error instead of filling the buffer forever
+        self.receiver.close();
+    }
+}
+
+pub(super) struct RestartableSink {
+    pub name: ComponentName,
+    pub config: crate::sinks::Config,
+    pub receiver: ReceiverSlot,
+}
+
+impl Restartable for RestartableSink {
+    async fn restart(&mut self) -> Result<Handler, RestartError> {
+        let sink = self
+            .config
+            .clone()
+            .build()
+            .await
+            .map_err(BuildError::from)?;
+        let receiver = self.receiver.take().ok_or(RestartError::ReceiverInUse)?;
+        Ok(sink
+            .start(&self.name, receiver)
+            .await
+            .map_err(StartingError::from)?)
+    }
+
+    fn abandon(&mut self) {
+        self.receiver.close();
+    }
+}
+
+/// Aborts the task of the component when the supervisor gets aborted.
+struct AbortOnDrop(Handler);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Watches the task of a component and starts it again, according to its restart policy,
+/// when it stops before being asked to.
+///
+/// The returned handler finishes once the component is stopped for good. Aborting it aborts
+/// the component as well. The `running` flag follows the state of the component, including
+/// while it waits to be restarted.
+pub(super) fn supervise<R: Restartable>(
+    name: ComponentName,
+    kind: &'static str,
+    restart: RestartConfig,
+    stop: CancellationToken,
+    handler: Handler,
+    running: Arc<AtomicBool>,
+    mut component: R,
+) -> JoinHandle<()> {
+    // wrapped before spawning, the supervisor can be aborted before being polled
+    let mut handler = AbortOnDrop(handler);
+    running.store(true, Ordering::SeqCst);
+    tokio::spawn(async move {
+        let mut started_at = Instant::now();
+        let initial_backoff = Duration::from_millis(restart.initial_backoff);
+        let max_backoff = Duration::from_millis(restart.max_backoff.max(restart.initial_backoff));
+        let mut backoff = initial_backoff;
+        loop {
+            let result = (&mut handler.0).await;
+            running.store(false, Ordering::SeqCst);
+            if stop.is_cancelled() {
+                match result {
+                    Ok(Ok(_)) => {}
+                    Ok(Err(err)) => tracing::error!("{kind} {name} failed while stopping: {err}"),
+                    Err(err) => tracing::error!("{kind} {name} crashed while stopping: {err:?}"),
+                }
+                return;
+            }
+            let failed = match result {
+                Ok(Ok(_)) => {
+                    tracing::warn!("{kind} {name} stopped unexpectedly");
+                    false
+                }
+                Ok(Err(err)) => {
+                    tracing::error!("{kind} {name} failed: {err}");
+                    true
+                }
+                Err(err) => {
+                    tracing::error!("{kind} {name} crashed: {err:?}");
+                    true
+                }
+            };
+            if !restart.should_restart(failed) {
+                tracing::warn!("{kind} {name} will not be restarted");
+                component.abandon();
+                return;
+            }
+            if started_at.elapsed() >= max_backoff {
+                backoff = initial_backoff;
+            }
+            loop {
+                tracing::info!("restarting {kind} {name} in {backoff:?}");
+                tokio::select! {
+                    _ = tokio::time::sleep(backoff) => {}
+                    _ = stop.cancelled() => return,
+                }
+                backoff = backoff.saturating_mul(2).min(max_backoff);
+                match component.restart().await {
+                    Ok(next) => {
+                        handler = AbortOnDrop(next);
+                        running.store(true, Ordering::SeqCst);
+                        started_at = Instant::now();
+                        break;
+                    }
+                    Err(err) => tracing::error!("unable to restart {kind} {name}: {err:?}"),
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use tokio::sync::mpsc::error::SendError;
+    use tokio::time::Duration;
+    use tokio_util::sync::CancellationToken;
+
+    use super::{supervise, Handler, RestartConfig, RestartError, RestartPolicy, Restartable};
+    use crate::components::name::ComponentName;
+    use crate::components::ExecutionError;
+
+    struct Counting {
+        counter: Arc<AtomicUsize>,
+        task: fn() -> Handler,
+    }
+
+    impl Restartable for Counting {
+        async fn restart(&mut self) -> Result<Handler, RestartError> {
+            self.counter.fetch_add(1, Ordering::SeqCst);
+            Ok((self.task)())
+        }
+    }
+
+    fn config(policy: RestartPolicy) -> RestartConfig {
+        RestartConfig {
+            policy,
+            initial_backoff: 10,
+            max_backoff: 20,
+        }
+    }
+
+    fn crashing() -> Handler {
+        tokio::spawn(async { panic!("component crashed") })
+    }
+
+    fn failing() -> Handler {
+        tokio::spawn(async {
+            let event = crate::event::log::EventLog::new("hello").into();
+            Err(ExecutionError::UnableToSend(SendError(event)))
+        })
+    }
+
+    fn exiting() -> Handler {
+        tokio::spawn(async { Ok(()) })
+    }
+
+    async fn count_starts(policy: RestartPolicy, task: fn() -> Handler) -> usize {
+        let counter = Arc::new(AtomicUsize::new(1));
+        let stop = CancellationToken::new();
+        let handler = supervise(
+            ComponentName::new("component"),
+            "source",
+            config(policy),
+            stop.clone(),
+            task(),
+            Default::default(),
+            Counting {
+                counter: counter.clone(),
+                task,
+            },
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        stop.cancel();
+        tokio::time::timeout(Duration::from_secs(1), handler)
+            .await
+            .unwrap()
+            .unwrap();
+        counter.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn should_never_restart() {
+        assert_eq!(count_starts(RestartPolicy::Never, crashing).await, 1);
+    }
+
+    #[tokio::test]
+    async fn should_restart_on_failure_with_backoff() {
+        let starts = count_starts(RestartPolicy::OnFailure, crashing).await;
+        // 10ms, then 20ms between each restart
+        assert!((3..=7).contains(&starts), "started {starts} times");
+    }
+
+    #[tokio::test]
+    async fn should_restart_on_failure_when_returning_early() {
+        let starts = count_starts(RestartPolicy::OnFailure, failing).await;
+        assert!((3..=7).contains(&starts), "started {starts} times");
+        assert_eq!(count_starts(RestartPolicy::Never, failing).await, 1);
+        // returning normally isn't a failure
+        assert_eq!(count_starts(RestartPolicy::OnFailure, exiting).await, 1);
+        assert!(count_starts(RestartPolicy::Always, exiting).await > 1);
+    }
+
+    #[tokio::test]
+    async fn should_follow_running_state() {
+        let running = Arc::new(AtomicBool::new(false));
+        let stop = CancellationToken::new();
+        let handler = supervise(
+            ComponentName::new("component"),
+            "sink",
+            RestartConfig {
+                policy: RestartPolicy::Always,
+                initial_backoff: 1000,
+                max_backoff: 1000,
+            },
+            stop.clone(),
+            tokio::spawn(async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                Ok(())
+            }),
+            running.clone(),
+            Counting {
+                counter: Default::default(),
+                task: exiting,
+            },
+        );
+        assert!(running.load(Ordering::SeqCst));
+        // waiting for its backoff before being restarted
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!running.load(Ordering::SeqCst));
+        stop.cancel();
+        handler.await.unwrap();
+        assert!(!running.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn should_always_restart() {
+        assert!(count_starts(RestartPolicy::Always, exiting).await > 1);
+    }
+
+    #[tokio::test]
+    async fn should_abort_component_with_supervisor() {
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let component = tokio::spawn(async move {
+            let _tx = tx;
+            std::future::pending().await
+        });
+        let handler = supervise(
+            ComponentName::new("component"),
+            "sink",
+            config(RestartPolicy::Always),
+            CancellationToken::new(),
+            component,
+            Default::default(),
+            Counting {
+                counter: Default::default(),
+                task: exiting,
+            },
+        );
+        handler.abort();
+        // the sender gets dropped with the aborted component
+        tokio::time::timeout(Duration::from_secs(1), rx)
+            .await
+            .unwrap()
+            .unwrap_err();
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+
+use tokio::time::Duration;
+
+use super::{wire, BuildError, Config, StartingError};
+use crate::components::buffer::{memory, WhenFull};
+use crate::components::name::ComponentName;
+use crate::components::output::{ComponentOutput, ComponentWithOutputs};
+use crate::event::Event;
+use crate::prelude::{Receiver, Sender};
+
+/// Capacity of the channels between the transforms under test.
+const CHANNEL_CAPACITY: usize = 100;
+/// Maximum time for the injected events to go through the transforms.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Events injected into the input of a transform.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+pub struct TestInput {
+    insert_at: ComponentName,
+    #[serde(default)]
+    events: Vec<Event>,
+}
+
+/// Events expected to leave an output, in order.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+pub struct TestOutput {
+    extract_from: ComponentOutput<'static>,
+    #[serde(default)]
+    events: Vec<Event>,
+}
+
+/// Checks the events produced by the transforms of the configuration, without starting
+/// any source or sink.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+pub struct TestConfig {
+    pub name: String,
+    #[serde(default)]
+    inputs: Vec<TestInput>,
+    #[serde(default)]
+    outputs: Vec<TestOutput>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TestError {
+    #[error("unable to insert events at {0}, only transforms can be tested")]
+    UnknownTransform(ComponentName),
+    #[error("unable to extract events from {0}, only transform outputs can be tested")]
+    UnknownOutput(ComponentOutput<'static>),
+    #[error(transparent)]
+    Build(#[from] BuildError),
+    #[error(transparent)]
+    Starting(#[from] StartingError),
+    #[error("the events didn't go through the transforms within {0:?}")]
+    Timeout(Duration),
+    #[error("unexpected events at {output}\n{diff}")]
+    Mismatch {
+        output: ComponentOutput<'static>,
+        diff: String,
+    },
+}
+
+fn to_pretty_json(events: &[Event]) -> String {
+    serde_json::to_string_pretty(events).unwrap_or_default()
+}
+
+/// Line diff of the expected and actual contents, based on their longest common subsequence.
+fn diff(expected: &str, actual: &str) -> String {
+    let expected = expected.lines().collect::<Vec<_>>();
+    let actual = actual.lines().collect::<Vec<_>>();
+    // lengths[i][j] is the length of the common subsequence of expected[i..] and actual[j..]
+    let mut lengths = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lengths[i][j] = match expected[i] == actual[j] {
+                true => lengths[i + 1][j + 1] + 1,
+                false => lengths[i + 1][j].max(lengths[i][j + 1]),
+            };
+        }
+    }
+    let mut result = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            result.push_str(&format!("  {}\n", expected[i]));
+            i += 1;
+            j += 1;
+        } else if j == actual.len()
+            || (i < expected.len() && lengths[i + 1][j] >= lengths[i][j + 1])
+        {
+            result.push_str(&format!("- {}\n", expected[i]));
+            i += 1;
+        } else {
+            result.push_str(&format!("+ {}\n", actual[j]));
+            j += 1;
+        }
+    }
+    result
+}
+
+async fn collect(mut receiver: Receiver) -> Vec<Event> {
+    let mut events = Vec::new();
+    while let Some(event) = receiver.recv().await {
+        events.push(event);
+    }
+    events
+}
+
+impl TestConfig {
+    fn check(&self, config: &Config) -> Result<(), TestError> {
+        if let Some(input) = self
+            .inputs
+            .iter()
+            .find(|input| !config.transforms.contains_key(&input.insert_at))
+        {
+            return Err(TestError::UnknownTransform(input.insert_at.clone()));
+        }
+        if let Some(output) = self.outputs.iter().find(|output| {
+            !config
+                .transforms
+                .get(output.extract_from.name.as_ref())
+                .is_some_and(|transform| transform.inner.has_output(&output.extract_from.output))
+        }) {
+            return Err(TestError::UnknownOutput(output.extract_from.clone()));
+        }
+        Ok(())
+    }
+
+    /// Injects the input events into the transforms, wired as in the topology, and compares
+    /// the events leaving the tested outputs with the expected ones.
+    pub async fn run(&self, config: &Config) -> Result<(), TestError> {
+        self.check(config)?;
+
+        let mut senders = HashMap::with_capacity(config.transforms.len());
+        let mut receivers = HashMap::with_capacity(config.transforms.len());
+        for name in config.transforms.keys() {
+            let (sender, receiver) = memory::channel(CHANNEL_CAPACITY, WhenFull::Block);
+            senders.insert(name.clone(), Sender::Memory(sender));
+            receivers.insert(name.clone(), Receiver::from(receiver));
+        }
+        // the collectors of the sources are dropped, only the injected events go through
+        let mut collectors = wire(config, &senders);
+        let mut captures = Vec::with_capacity(self.outputs.len());
+        for output in self.outputs.iter() {
+            let (sender, receiver) = memory::channel(CHANNEL_CAPACITY, WhenFull::Block);
+            collectors
+                .entry(output.extract_from.to_owned_name())
+                .or_default()
+                .add_output(
+                    output.extract_from.to_owned_output(),
+                    Sender::Memory(sender),
+                );
+            captures.push(tokio::spawn(collect(Receiver::from(receiver))));
+        }
+
+        let mut handlers = Vec::with_capacity(config.transforms.len());
+        for (name, transform) in config.transforms.iter() {
+            let receiver = receivers.remove(name).expect("receiver created above");
+            let collector = collectors.remove(name).unwrap_or_default();
+            let transform = transform.inner.clone().build().map_err(BuildError::from)?;
+            let handler = transform.start(name, receiver, collector).await;
+            handlers.push(handler.map_err(StartingError::from)?);
+        }
+        drop(collectors);
+
+        // the transforms stop once all the senders are dropped, closing the captures
+        let outcome = tokio::time::timeout(TIMEOUT, async move {
+            for input in self.inputs.iter() {
+                let sender = &senders[&input.insert_at];
+                for event in input.events.iter() {
+                    if sender.send(event.clone()).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            drop(senders);
+            let mut received = Vec::with_capacity(captures.len());
+            for capture in captures {
+                received.push(capture.await.unwrap_or_default());
+            }
+            received
+        })
+        .await;
+        let received = match outcome {
+            Ok(received) => received,
+            Err(_) => {
+                handlers.iter().for_each(|handler| handler.abort());
+                return Err(TestError::Timeout(TIMEOUT));
+            }
+        };
+
+        for (output, mut actual) in self.outputs.iter().zip(received) {
+            // the metadata is only checked when the expected event defines some
+            for (expected, event) in output.events.iter().zip(actual.iter_mut()) {
+                if expected.metadata().is_empty() {
+                    *event.metadata_mut() = Default::default();
+                }
+            }
+            if output.events != actual {
+                return Err(TestError::Mismatch {
+                    output: output.extract_from.clone(),
+                    diff: diff(&to_pretty_json(&output.events), &to_pretty_json(&actual)),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, TestError};
+    use crate::topology::Config;
+
+    const CONFIG: &str = r#"
+[sources.generator]
+type = "random_logs"
+
+[transforms.only_errors]
+type = "filter"
+inputs = ["generator"]
+condition = { type = "has_attribute", name = "error" }
+
+[transforms.tagged]
+type = "add_fields"
+inputs = ["only_errors"]
+fields = { team = "backend" }
+
+[sinks.output]
+type = "black_hole"
+inputs = ["tagged"]
+
+[[tests]]
+name = "keeps the errors"
+
+[[tests.inputs]]
+insert_at = "only_errors"
+events = [
+    { type = "log", content = { message = "hello" } },
+    { type = "log", content = { message = "boom", error = "timeout" } },
+]
+
+[[tests.outputs]]
+extract_from = "tagged"
+events = [
+    { type = "log", content = { message = "boom", error = "timeout", team = "backend" } },
+]
+"#;
+
+    #[tokio::test]
+    async fn should_pass_with_expected_events() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        let config = config.validate().unwrap();
+        config.tests[0].run(&config).await.unwrap();
+    }
+
+    fn with_expected_hops(hops: &str) -> Config {
+        let expected = format!(r#"team = "backend" }}, metadata = {{ hops = {hops} }} }},"#);
+        toml::from_str(&CONFIG.replace(r#"team = "backend" } },"#, &expected)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_check_metadata_when_expected() {
+        let config = with_expected_hops(r#"["only_errors", "tagged"]"#);
+        config.tests[0].run(&config).await.unwrap();
+        let config = with_expected_hops(r#"["tagged"]"#);
+        let error = config.tests[0].run(&config).await.unwrap_err();
+        assert!(matches!(error, TestError::Mismatch { .. }));
+    }
+
+    #[tokio::test]
+    async fn should_fail_with_unexpected_events() {
+        let config: Config =
+            toml::from_str(&CONFIG.replace("team = \"backend\" } }", "team = \"frontend\" } }"))
+                .unwrap();
+        let error = config.tests[0].run(&config).await.unwrap_err();
+        let TestError::Mismatch { output, diff } = error else {
+            panic!("unexpected error {error:?}");
+        };
+        assert_eq!(output.to_string(), "tagged");
+        assert!(diff.contains("-       \"team\": \"frontend\""), "{diff}");
+        assert!(diff.contains("+       \"team\": \"backend\""), "{diff}");
+    }
+
+    #[tokio::test]
+    async fn should_only_test_transforms() {
+        let config: Config = toml::from_str(
+            &CONFIG.replace("insert_at = \"only_errors\"", "insert_at = \"output\""),
+        )
+        .unwrap();
+        let error = config.tests[0].run(&config).await.unwrap_err();
+        assert!(matches!(error, TestError::UnknownTransform(name) if name.as_ref() == "output"));
+    }
+
+    #[test]
+    fn should_diff_lines() {
+        assert_eq!(diff("a\nb\nc", "a\nc\nd"), "  a\n- b\n  c\n+ d\n");
+    }
+}
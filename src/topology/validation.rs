@@ -1,16 +1,12 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 
 use super::{Config, WithInputs};
+use crate::components::buffer::{BufferType, WhenFull};
 use crate::components::name::ComponentName;
 use crate::components::output::{ComponentOutput, ComponentWithOutputs, NamedOutput};
 
 #[derive(Debug, PartialEq, Eq, thiserror::Error)]
 pub enum ValidationError {
-    #[error("the same input {input} is being used by multiple components {targets:?}")]
-    MultipleUseOfInput {
-        input: ComponentOutput<'static>,
-        targets: HashSet<ComponentName>,
-    },
     #[error("unable to find the specified input {input}")]
     InputNotFound { input: ComponentOutput<'static> },
     #[error("unable to find output {output} in the component {name}")]
@@ -20,7 +16,6 @@ pub enum ValidationError {
     },
     #[error("component {name} should have at least one input")]
     NoInput { name: ComponentName },
-    // In theory, this is never reachable considering an output can only be used once.
     #[error("circular dependency detected in the path {path:?} with {name}")]
     CircularDependency {
         path: HashSet<ComponentName>,
@@ -28,10 +23,13 @@ pub enum ValidationError {
     },
     #[error("component {name} is not part of a route that goes from a source to a sink")]
     OrphanComponent { name: ComponentName },
+    #[error("invalid buffer for component {name}: {reason}")]
+    InvalidBuffer {
+        name: ComponentName,
+        reason: &'static str,
+    },
 }
 
-type RelationMap<'a> = HashMap<ComponentOutput<'a>, HashSet<&'a ComponentName>>;
-
 enum Node<'a> {
     Source {
         config: &'a crate::sources::Config,
@@ -83,7 +81,6 @@ impl<'a> Node<'a> {
 struct Graph<'a> {
     config: &'a super::Config,
     nodes: HashMap<&'a ComponentName, Node<'a>>,
-    relations: RelationMap<'a>,
 }
 
 impl<'a> Graph<'a> {
@@ -91,7 +88,7 @@ impl<'a> Graph<'a> {
         let nodes = config
             .sources
             .iter()
-            .map(|(name, source)| (name, Node::source(source)))
+            .map(|(name, source)| (name, Node::source(&source.inner)))
             .chain(
                 config
                     .transforms
@@ -105,27 +102,24 @@ impl<'a> Graph<'a> {
                     .map(|(name, sink)| (name, Node::sink(sink))),
             )
             .collect();
-        let relations = config
-            .sinks
-            .iter()
-            .flat_map(|(name, sink)| sink.inputs.iter().map(move |input| (input, name)))
-            .fold(RelationMap::new(), |mut res, (input, name)| {
-                res.entry(input.to_borrowed()).or_default().insert(name);
-                res
-            });
-        Self {
-            config,
-            nodes,
-            relations,
-        }
+        Self { config, nodes }
     }
 
-    fn check_multiple_use_of_input(&self, errors: &mut Vec<ValidationError>) {
-        for (output, targets) in self.relations.iter() {
-            if targets.len() > 1 {
-                errors.push(ValidationError::MultipleUseOfInput {
-                    input: output.to_owned(),
-                    targets: HashSet::from_iter(targets.iter().map(|item| (*item).clone())),
+    fn check_buffers(&self, errors: &mut Vec<ValidationError>) {
+        for (name, transform) in self.config.transforms.iter() {
+            if transform.buffer.kind == BufferType::Disk {
+                errors.push(ValidationError::InvalidBuffer {
+                    name: name.clone(),
+                    reason: "disk buffers are only available for sinks",
+                });
+            }
+        }
+        for (name, sink) in self.config.sinks.iter() {
+            if sink.buffer.kind == BufferType::Disk && sink.buffer.when_full == WhenFull::DropOldest
+            {
+                errors.push(ValidationError::InvalidBuffer {
+                    name: name.clone(),
+                    reason: "disk buffers cannot drop the oldest events",
                 });
             }
         }
@@ -192,8 +186,8 @@ impl Config {
         let mut errors = Vec::new();
 
         let graph = Graph::build(&self);
-        graph.check_multiple_use_of_input(&mut errors);
         graph.traverse_backward(&mut errors);
+        graph.check_buffers(&mut errors);
 
         if errors.is_empty() {
             Ok(self)
@@ -214,42 +208,95 @@ mod tests {
     use crate::topology::{Config, WithInputs};
 
     #[test]
-    fn component_output_shouldnt_be_used_more_than_once() {
+    fn component_output_can_be_used_more_than_once() {
         let mut config = Config::default();
         config.sources.insert(
             ComponentName::new("foo"),
-            crate::sources::Config::RandomLogs(crate::sources::random_logs::Config::default()),
+            crate::sources::random_logs::Config::default().into(),
         );
         config.sinks.insert(
             ComponentName::new("bar"),
-            WithInputs {
-                inner: crate::sinks::Config::BlackHole(crate::sinks::black_hole::Config::default()),
-                inputs: HashSet::from_iter([ComponentOutput {
-                    name: Cow::Owned(ComponentName::new("foo")),
-                    output: Cow::Owned(NamedOutput::Default),
-                }]),
-            },
+            WithInputs::new(crate::sinks::black_hole::Config::default()).with_default_input("foo"),
         );
         config.sinks.insert(
             ComponentName::new("baz"),
-            WithInputs {
-                inner: crate::sinks::Config::BlackHole(crate::sinks::black_hole::Config::default()),
-                inputs: HashSet::from_iter([ComponentOutput {
-                    name: Cow::Owned(ComponentName::new("foo")),
-                    output: Cow::Owned(NamedOutput::Default),
-                }]),
-            },
+            WithInputs::new(crate::sinks::black_hole::Config::default()).with_default_input("foo"),
+        );
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn circular_dependency() {
+        let mut config = Config::default();
+        config.sources.insert(
+            ComponentName::new("foo"),
+            crate::sources::random_logs::Config::default().into(),
+        );
+        config.transforms.insert(
+            ComponentName::new("first"),
+            WithInputs::new(crate::transforms::route::Config::default())
+                .with_default_input("foo")
+                .with_named_input("second", "dropped"),
+        );
+        config.transforms.insert(
+            ComponentName::new("second"),
+            WithInputs::new(crate::transforms::route::Config::default())
+                .with_named_input("first", "dropped"),
+        );
+        config.sinks.insert(
+            ComponentName::new("bar"),
+            WithInputs::new(crate::sinks::black_hole::Config::default())
+                .with_named_input("first", "dropped"),
         );
         let errors = config.validate().unwrap_err();
+        assert!(errors.contains(&ValidationError::CircularDependency {
+            path: HashSet::from_iter([
+                ComponentName::new("bar"),
+                ComponentName::new("first"),
+                ComponentName::new("second"),
+            ]),
+            name: ComponentName::new("first"),
+        }));
+    }
+
+    #[test]
+    fn disk_buffer_only_for_sinks() {
+        use crate::components::buffer::{BufferConfig, BufferType, WhenFull};
+
+        let disk = |when_full| BufferConfig {
+            kind: BufferType::Disk,
+            when_full,
+            ..Default::default()
+        };
+        let mut config = Config::default();
+        config.sources.insert(
+            ComponentName::new("foo"),
+            crate::sources::random_logs::Config::default().into(),
+        );
+        let mut transform =
+            WithInputs::new(crate::transforms::route::Config::default()).with_default_input("foo");
+        transform.buffer = disk(WhenFull::Block);
+        config
+            .transforms
+            .insert(ComponentName::new("router"), transform);
+        let mut sink = WithInputs::new(crate::sinks::black_hole::Config::default())
+            .with_named_input("router", "dropped");
+        sink.buffer = disk(WhenFull::DropOldest);
+        config.sinks.insert(ComponentName::new("bar"), sink);
+        let mut errors = config.validate().unwrap_err();
+        errors.sort_by_key(|error| error.to_string());
         assert_eq!(
             errors,
-            vec![ValidationError::MultipleUseOfInput {
-                input: ComponentOutput {
-                    name: Cow::Owned(ComponentName::new("foo")),
-                    output: Cow::Owned(NamedOutput::Default),
+            vec![
+                ValidationError::InvalidBuffer {
+                    name: ComponentName::new("bar"),
+                    reason: "disk buffers cannot drop the oldest events",
                 },
-                targets: HashSet::from_iter([ComponentName::new("baz"), ComponentName::new("bar")])
-            }]
+                ValidationError::InvalidBuffer {
+                    name: ComponentName::new("router"),
+                    reason: "disk buffers are only available for sinks",
+                },
+            ]
         );
     }
 
@@ -260,6 +307,8 @@ mod tests {
             ComponentName::new("bar"),
             WithInputs {
                 inner: crate::sinks::Config::BlackHole(crate::sinks::black_hole::Config::default()),
+                buffer: Default::default(),
+                restart: Default::default(),
                 inputs: HashSet::new(),
             },
         );
@@ -282,16 +331,18 @@ mod tests {
         let mut config = Config::default();
         config.sources.insert(
             ComponentName::new("foo"),
-            crate::sources::Config::RandomLogs(crate::sources::random_logs::Config::default()),
+            crate::sources::random_logs::Config::default().into(),
         );
         config.sources.insert(
             ComponentName::new("orphan"),
-            crate::sources::Config::RandomLogs(crate::sources::random_logs::Config::default()),
+            crate::sources::random_logs::Config::default().into(),
         );
         config.sinks.insert(
             ComponentName::new("bar"),
             WithInputs {
                 inner: crate::sinks::Config::BlackHole(crate::sinks::black_hole::Config::default()),
+                buffer: Default::default(),
+                restart: Default::default(),
                 inputs: HashSet::from_iter([ComponentOutput {
                     name: Cow::Owned(ComponentName::new("foo")),
                     output: Cow::Owned(NamedOutput::Default),
@@ -1,15 +1,24 @@
 use indexmap::IndexMap;
 
 use crate::components::output::ComponentWithOutputs;
+use crate::event::path::{AttributePath, PathError};
 use crate::event::Event;
 use crate::prelude::StringOrEnv;
 
 #[derive(Debug, thiserror::Error)]
-pub enum BuildError {}
+pub enum BuildError {
+    #[error("invalid field path")]
+    InvalidPath(
+        #[from]
+        #[source]
+        PathError,
+    ),
+}
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 #[cfg_attr(test, derive(Default))]
 pub struct Config {
+    /// Keyed by path for the logs, like `http.request.method`, and by tag for the metrics
     fields: IndexMap<String, StringOrEnv>,
 }
 
@@ -17,18 +26,18 @@ impl ComponentWithOutputs for Config {}
 
 impl Config {
     pub fn build(self) -> Result<Transform, BuildError> {
-        Ok(Transform {
-            fields: self
-                .fields
-                .into_iter()
-                .filter_map(|(name, value)| value.into_string().map(|v| (name, v)))
-                .collect(),
-        })
+        let fields = self
+            .fields
+            .into_iter()
+            .filter_map(|(name, value)| value.into_string().map(|v| (name, v)))
+            .map(|(name, value)| Ok((name.parse()?, name, value)))
+            .collect::<Result<_, PathError>>()?;
+        Ok(Transform { fields })
     }
 }
 
 pub struct Transform {
-    fields: IndexMap<String, String>,
+    fields: Vec<(AttributePath, String, String)>,
 }
 
 impl Transform {
@@ -41,17 +50,51 @@ impl super::Executable for Transform {
     fn transform(&self, event: Event) -> Event {
         match event {
             Event::Log(mut inner) => {
-                for (name, value) in self.fields.iter() {
-                    inner.add_attribute(name.clone(), value.clone());
+                for (path, name, value) in self.fields.iter() {
+                    if !inner.insert_path(path, value.clone().into()) {
+                        tracing::debug!("unable to add field {name:?}, a parent isn't an object");
+                    }
                 }
                 Event::Log(inner)
             }
-            Event::Metric(mut inner) => {
-                for (name, value) in self.fields.iter() {
-                    inner.add_tag(name.clone(), value.clone());
+            Event::Metric(inner) => Event::Metric(inner.with_tags_mutation(|tags| {
+                for (_, name, value) in self.fields.iter() {
+                    tags.insert(name.clone().into(), value.clone().into());
                 }
-                Event::Metric(inner)
-            }
+            })),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::event::log::EventLog;
+    use crate::transforms::Executable;
+
+    #[test]
+    fn should_add_nested_fields() {
+        let config: super::Config = toml::from_str(
+            r#"fields = { "http.request.method" = "GET", "http.status" = "200", service = "api" }"#,
+        )
+        .unwrap();
+        let transform = config.build().unwrap();
+
+        let event = transform.transform(EventLog::new("hello").into());
+        let event = event.into_event_log().unwrap();
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({
+                "message": "hello",
+                "http": {"request": {"method": "GET"}, "status": "200"},
+                "service": "api",
+            })
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_path() {
+        let config: super::Config =
+            toml::from_str(r#"fields = { "http..method" = "GET" }"#).unwrap();
+        assert!(config.build().is_err());
+    }
+}
@@ -7,7 +7,7 @@ use crate::event::Event;
 #[derive(Debug, thiserror::Error)]
 pub enum BuildError {}
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 #[cfg_attr(test, derive(Default))]
 pub struct Config {}
 
@@ -0,0 +1,26 @@
+use crate::components::name::ComponentName;
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+pub struct Config {
+    name: ComponentName,
+}
+
+impl super::prelude::Builder for Config {
+    type Output = Condition;
+    type Error = super::BuildError;
+
+    fn build(self) -> Result<Self::Output, Self::Error> {
+        Ok(Condition { name: self.name })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Condition {
+    name: ComponentName,
+}
+
+impl super::prelude::Evaluate for Condition {
+    fn evaluate(&self, event: &crate::event::Event) -> bool {
+        event.metadata().source.as_ref() == Some(&self.name)
+    }
+}
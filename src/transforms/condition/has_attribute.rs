@@ -1,5 +1,8 @@
-#[derive(Clone, Debug, serde::Deserialize)]
+use crate::event::path::AttributePath;
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 pub struct Config {
+    /// Path of the attribute, like `http.request.headers[0]`
     name: String,
 }
 
@@ -8,19 +11,21 @@ impl super::prelude::Builder for Config {
     type Error = super::BuildError;
 
     fn build(self) -> Result<Self::Output, Self::Error> {
-        Ok(Condition { name: self.name })
+        Ok(Condition {
+            path: self.name.parse()?,
+        })
     }
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug)]
 pub struct Condition {
-    name: String,
+    path: AttributePath,
 }
 
 impl super::prelude::Evaluate for Condition {
     fn evaluate(&self, event: &crate::event::Event) -> bool {
         event
             .as_event_log()
-            .map_or(false, |log| log.attributes.contains_key(self.name.as_str()))
+            .is_some_and(|log| log.get_path(&self.path).is_some())
     }
 }
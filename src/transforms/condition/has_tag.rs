@@ -1,17 +1,20 @@
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum CheckConfig {
+    #[default]
     Exists,
-    Equals { value: String },
-    EndsWith { value: String },
-    Matches { regex: String },
-    StartsWith { value: String },
-}
-
-impl Default for CheckConfig {
-    fn default() -> Self {
-        Self::Exists
-    }
+    Equals {
+        value: String,
+    },
+    EndsWith {
+        value: String,
+    },
+    Matches {
+        regex: String,
+    },
+    StartsWith {
+        value: String,
+    },
 }
 
 impl CheckConfig {
@@ -37,7 +40,7 @@ pub enum Check {
     StartsWith { value: String },
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 pub struct Config {
     name: String,
     #[serde(default)]
@@ -64,28 +67,28 @@ pub struct Condition {
 
 impl super::prelude::Evaluate for Condition {
     fn evaluate(&self, event: &crate::event::Event) -> bool {
-        event.as_event_metric().map_or(false, |m| match self.check {
+        event.as_event_metric().is_some_and(|m| match self.check {
             Check::Exists => m.header.tags.contains_key(self.name.as_str()),
             Check::Equals { ref value } => m
                 .header
                 .tags
                 .get(self.name.as_str())
-                .map_or(false, |v| value.eq(v.as_ref())),
+                .is_some_and(|v| value.eq(v.as_ref())),
             Check::EndsWith { ref value } => m
                 .header
                 .tags
                 .get(self.name.as_str())
-                .map_or(false, |v| v.ends_with(value)),
+                .is_some_and(|v| v.ends_with(value)),
             Check::Matches { ref regex } => m
                 .header
                 .tags
                 .get(self.name.as_str())
-                .map_or(false, |v| regex.is_match(v.as_ref())),
+                .is_some_and(|v| regex.is_match(v.as_ref())),
             Check::StartsWith { ref value } => m
                 .header
                 .tags
                 .get(self.name.as_str())
-                .map_or(false, |v| v.starts_with(value)),
+                .is_some_and(|v| v.starts_with(value)),
         })
     }
 }
@@ -1,4 +1,4 @@
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 pub struct Config;
 
 impl super::prelude::Builder for Config {
@@ -3,6 +3,7 @@ use self::prelude::{Builder, Evaluate};
 pub mod prelude;
 
 mod and;
+mod from_source;
 mod has_attribute;
 mod has_tag;
 mod is_log;
@@ -11,12 +12,20 @@ mod not;
 mod or;
 
 #[derive(Debug, thiserror::Error)]
-pub enum BuildError {}
+pub enum BuildError {
+    #[error("invalid attribute path")]
+    InvalidPath(
+        #[from]
+        #[source]
+        crate::event::path::PathError,
+    ),
+}
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum Config {
     And(self::and::Config),
+    FromSource(self::from_source::Config),
     HasAttribute(self::has_attribute::Config),
     HasTag(self::has_tag::Config),
     IsLog(self::is_log::Config),
@@ -40,6 +49,7 @@ impl Config {
     pub fn build(self) -> Result<Condition, BuildError> {
         Ok(match self {
             Self::And(inner) => Condition::And(inner.build()?),
+            Self::FromSource(inner) => Condition::FromSource(inner.build()?),
             Self::HasAttribute(inner) => Condition::HasAttribute(inner.build()?),
             Self::HasTag(inner) => Condition::HasTag(inner.build()?),
             Self::IsLog(inner) => Condition::IsLog(inner.build()?),
@@ -56,6 +66,7 @@ pub enum Condition {
     And(self::and::Condition),
     Or(self::or::Condition),
     Not(self::not::Condition),
+    FromSource(self::from_source::Condition),
     // Metrics related
     HasAttribute(has_attribute::Condition),
     HasTag(has_tag::Condition),
@@ -95,12 +106,30 @@ mod tests {
         &[Event::Log(EventLog::new("hello world")), Event::Metric(EventMetric::new(0, "foo", "bar", EventMetricValue::Gauge(12.34)))];
         "has_attribute condition"
     )]
+    #[test_case::test_case(
+        r#"{"type":"has_attribute", "name": "http.headers[1]"}"#,
+        &[Event::Log(EventLog::new("hello world").with_attribute("http", serde_json::from_str::<crate::event::log::EventLogAttribute>(r#"{"headers": ["accept", "host"]}"#).unwrap()))],
+        &[Event::Log(EventLog::new("hello world").with_attribute("http", serde_json::from_str::<crate::event::log::EventLogAttribute>(r#"{"headers": ["accept"]}"#).unwrap()))];
+        "has_attribute condition with path"
+    )]
+    #[test_case::test_case(
+        r#"{"type":"has_attribute", "name": "host.name"}"#,
+        &[Event::Log(EventLog::new("hello world").with_attribute("host.name", "a"))],
+        &[Event::Log(EventLog::new("hello world").with_attribute("host", "a"))];
+        "has_attribute condition with flat attribute"
+    )]
     #[test_case::test_case(
         r#"{"type":"is_metric"}"#,
         &[Event::Metric(EventMetric::new(0, "foo", "bar", EventMetricValue::Gauge(12.34)))],
         &[Event::Log(EventLog::new("hello world"))];
         "is_metric condition"
     )]
+    #[test_case::test_case(
+        r#"{"type":"from_source", "name": "generator"}"#,
+        &[Event::Log(EventLog::new("hello world").with_source("generator"))],
+        &[Event::Log(EventLog::new("hello world")), Event::Log(EventLog::new("hello world").with_source("other"))];
+        "from_source condition"
+    )]
     #[test_case::test_case(
         r#"{"type":"has_tag", "name": "foo"}"#,
         &[Event::Metric(EventMetric::new(0, "foo", "bar", EventMetricValue::Gauge(12.34)).with_tag("foo", "bar"))],
@@ -5,7 +5,8 @@ pub trait Builder {
     fn build(self) -> Result<Self::Output, Self::Error>;
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[allow(dead_code)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 pub struct WrappedConfig<T> {
     pub value: T,
 }
@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use tokio::sync::mpsc::error::SendError;
+
+use crate::components::collector::Collector;
+use crate::components::output::ComponentWithOutputs;
+use crate::event::metric::{
+    EventMetric, EventMetricHeader, EventMetricKind, EventMetricValue, HistogramBucket,
+};
+use crate::event::Event;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BuildError {}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+pub struct Config {
+    /// Kind of the metrics leaving the transform
+    into: EventMetricKind,
+}
+
+impl ComponentWithOutputs for Config {}
+
+impl Config {
+    pub fn build(self) -> Result<Transform, BuildError> {
+        Ok(Transform {
+            into: self.into,
+            previous: Default::default(),
+        })
+    }
+}
+
+/// Converts the counters, gauges and histograms, the other values being forwarded as they are.
+pub struct Transform {
+    into: EventMetricKind,
+    /// Last total seen for each metric, received when converting to incremental and
+    /// accumulated when converting to absolute
+    previous: Mutex<HashMap<EventMetricHeader, EventMetricValue>>,
+}
+
+impl Transform {
+    pub(crate) fn flavor(&self) -> &'static str {
+        "convert_metric_kind"
+    }
+
+    /// Returns nothing for the first total of a metric, the change being unknown.
+    fn handle_metric(&self, mut metric: EventMetric) -> Option<EventMetric> {
+        if metric.kind() == self.into || !is_convertible(&metric.value) {
+            return Some(metric);
+        }
+        let mut previous = self.previous.lock().unwrap();
+        match self.into {
+            EventMetricKind::Incremental => {
+                let last = previous.insert(metric.header.clone(), metric.value.clone())?;
+                metric.value = difference(&metric.value, &last)?;
+            }
+            EventMetricKind::Absolute => {
+                let total = match previous.remove(&metric.header) {
+                    Some(last) => sum(&last, &metric.value),
+                    None => None,
+                };
+                metric.value = total.unwrap_or(metric.value);
+                previous.insert(metric.header.clone(), metric.value.clone());
+            }
+        }
+        metric.kind = Some(self.into);
+        Some(metric)
+    }
+}
+
+fn is_convertible(value: &EventMetricValue) -> bool {
+    matches!(
+        value,
+        EventMetricValue::Counter(_)
+            | EventMetricValue::Gauge(_)
+            | EventMetricValue::Histogram { .. }
+    )
+}
+
+fn same_limits(left: &[HistogramBucket], right: &[HistogramBucket]) -> bool {
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right)
+            .all(|(l, r)| l.upper_limit == r.upper_limit)
+}
+
+/// Change from the last total, the current total being the change when the counter got
+/// reset. Nothing when the values can't be compared.
+fn difference(current: &EventMetricValue, last: &EventMetricValue) -> Option<EventMetricValue> {
+    match (current, last) {
+        (EventMetricValue::Counter(current), EventMetricValue::Counter(last)) => Some(
+            EventMetricValue::Counter(current.checked_sub(*last).unwrap_or(*current)),
+        ),
+        (EventMetricValue::Gauge(current), EventMetricValue::Gauge(last)) => {
+            Some(EventMetricValue::Gauge(current - last))
+        }
+        (
+            EventMetricValue::Histogram {
+                buckets,
+                count,
+                sum,
+            },
+            EventMetricValue::Histogram {
+                buckets: last_buckets,
+                count: last_count,
+                sum: last_sum,
+            },
+        ) if same_limits(buckets, last_buckets) => {
+            if count < last_count {
+                return Some(current.clone());
+            }
+            Some(EventMetricValue::Histogram {
+                buckets: buckets
+                    .iter()
+                    .zip(last_buckets)
+                    .map(|(bucket, last)| HistogramBucket {
+                        upper_limit: bucket.upper_limit,
+                        count: bucket.count.saturating_sub(last.count),
+                    })
+                    .collect(),
+                count: count - last_count,
+                sum: sum - last_sum,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Total after the change, nothing when the values can't be added.
+fn sum(total: &EventMetricValue, change: &EventMetricValue) -> Option<EventMetricValue> {
+    match (total, change) {
+        (EventMetricValue::Counter(total), EventMetricValue::Counter(change)) => {
+            Some(EventMetricValue::Counter(total.saturating_add(*change)))
+        }
+        (EventMetricValue::Gauge(total), EventMetricValue::Gauge(change)) => {
+            Some(EventMetricValue::Gauge(total + change))
+        }
+        (
+            EventMetricValue::Histogram {
+                buckets,
+                count,
+                sum,
+            },
+            EventMetricValue::Histogram {
+                buckets: change_buckets,
+                count: change_count,
+                sum: change_sum,
+            },
+        ) if same_limits(buckets, change_buckets) => Some(EventMetricValue::Histogram {
+            buckets: buckets
+                .iter()
+                .zip(change_buckets)
+                .map(|(bucket, change)| HistogramBucket {
+                    upper_limit: bucket.upper_limit,
+                    count: bucket.count.saturating_add(change.count),
+                })
+                .collect(),
+            count: count.saturating_add(*change_count),
+            sum: sum + change_sum,
+        }),
+        _ => None,
+    }
+}
+
+impl super::Executable for Transform {
+    async fn handle(&self, collector: &Collector, event: Event) -> Result<(), SendError<Event>>
+    where
+        Self: Sync,
+    {
+        match event {
+            Event::Log(inner) => collector.send_default(Event::Log(inner)).await,
+            Event::Metric(inner) => match self.handle_metric(inner) {
+                Some(metric) => collector.send_default(Event::Metric(metric)).await,
+                None => Ok(()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::metric::{EventMetric, EventMetricKind, EventMetricValue};
+
+    fn counter(value: u64, kind: EventMetricKind) -> EventMetric {
+        EventMetric::new(0, "http", "requests", EventMetricValue::Counter(value))
+            .with_tag("host", "fake-server")
+            .with_kind(kind)
+    }
+
+    fn values(transform: &super::Transform, metrics: Vec<EventMetric>) -> Vec<EventMetricValue> {
+        metrics
+            .into_iter()
+            .filter_map(|metric| transform.handle_metric(metric))
+            .map(|metric| {
+                assert_eq!(metric.kind(), transform.into);
+                metric.value
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_convert_to_incremental() {
+        let transform = super::Config {
+            into: EventMetricKind::Incremental,
+        }
+        .build()
+        .unwrap();
+        let metrics = [10, 15, 15, 3, 7]
+            .into_iter()
+            .map(|value| counter(value, EventMetricKind::Absolute))
+            .chain(std::iter::once(
+                counter(100, EventMetricKind::Absolute).with_tag("host", "other-server"),
+            ))
+            .collect();
+        // the first total gets dropped, the decrease being a reset
+        assert_eq!(
+            values(&transform, metrics),
+            vec![
+                EventMetricValue::Counter(5),
+                EventMetricValue::Counter(0),
+                EventMetricValue::Counter(3),
+                EventMetricValue::Counter(4),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_convert_to_absolute() {
+        let transform = super::Config {
+            into: EventMetricKind::Absolute,
+        }
+        .build()
+        .unwrap();
+        let metrics = [10, 5, 0]
+            .into_iter()
+            .map(|value| counter(value, EventMetricKind::Incremental))
+            .collect();
+        assert_eq!(
+            values(&transform, metrics),
+            vec![
+                EventMetricValue::Counter(10),
+                EventMetricValue::Counter(15),
+                EventMetricValue::Counter(15),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_forward_other_metrics() {
+        let transform = super::Config {
+            into: EventMetricKind::Incremental,
+        }
+        .build()
+        .unwrap();
+        let value = EventMetricValue::Set {
+            values: ["alice".to_string()].into(),
+        };
+        let metric =
+            EventMetric::new(0, "http", "users", value).with_kind(EventMetricKind::Absolute);
+        assert_eq!(transform.handle_metric(metric.clone()), Some(metric));
+    }
+}
@@ -16,7 +16,7 @@ fn default_fallback() -> NamedOutput {
     NamedOutput::Named("dropped".into())
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 pub struct Config {
     condition: crate::transforms::condition::Config,
     /// Route being used when condition is not matching.
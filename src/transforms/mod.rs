@@ -1,20 +1,24 @@
 use tokio::sync::mpsc::error::SendError;
 
 use crate::components::collector::Collector;
+use crate::components::metrics::COMPONENT_SPAN;
 use crate::components::name::ComponentName;
 use crate::components::output::{ComponentWithOutputs, NamedOutput};
+use crate::components::ExecutionError;
 use crate::event::Event;
 use crate::prelude::Receiver;
 
 pub mod add_fields;
 pub mod broadcast;
 pub mod condition;
+pub mod convert_metric_kind;
 pub mod filter;
+pub mod parse_timestamp;
 pub mod regex_parser;
 pub mod remove_fields;
 pub mod route;
 
-const COMPONENT_KIND: &str = "transform";
+pub(crate) const COMPONENT_KIND: &str = "transform";
 
 #[derive(Debug, thiserror::Error)]
 pub enum BuildError {
@@ -23,8 +27,12 @@ pub enum BuildError {
     #[error(transparent)]
     Broadcast(#[from] self::broadcast::BuildError),
     #[error(transparent)]
+    ConvertMetricKind(#[from] self::convert_metric_kind::BuildError),
+    #[error(transparent)]
     Filter(#[from] self::filter::BuildError),
     #[error(transparent)]
+    ParseTimestamp(#[from] self::parse_timestamp::BuildError),
+    #[error(transparent)]
     RegexParser(#[from] self::regex_parser::BuildError),
     #[error(transparent)]
     RemoveFields(#[from] self::remove_fields::BuildError),
@@ -32,25 +40,43 @@ pub enum BuildError {
     Route(#[from] self::route::BuildError),
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 #[enum_dispatch::enum_dispatch(ComponentWithOutputs)]
 #[serde(rename_all = "snake_case", tag = "type")]
 #[cfg_attr(test, derive(derive_more::From))]
 pub enum Config {
     AddFields(self::add_fields::Config),
     Broadcast(self::broadcast::Config),
+    ConvertMetricKind(self::convert_metric_kind::Config),
     Filter(self::filter::Config),
+    ParseTimestamp(self::parse_timestamp::Config),
     RegexParser(self::regex_parser::Config),
     RemoveFields(self::remove_fields::Config),
     Route(self::route::Config),
 }
 
 impl Config {
+    /// Type of the transform, as written in the configuration.
+    pub fn flavor(&self) -> &'static str {
+        match self {
+            Self::AddFields(_) => "add_fields",
+            Self::Broadcast(_) => "broadcast",
+            Self::ConvertMetricKind(_) => "convert_metric_kind",
+            Self::Filter(_) => "filter",
+            Self::ParseTimestamp(_) => "parse_timestamp",
+            Self::RegexParser(_) => "regex_parser",
+            Self::RemoveFields(_) => "remove_fields",
+            Self::Route(_) => "route",
+        }
+    }
+
     pub fn build(self) -> Result<Transform, BuildError> {
         Ok(match self {
             Self::AddFields(inner) => Transform::AddFields(inner.build()?),
             Self::Broadcast(inner) => Transform::Broadcast(inner.build()?),
+            Self::ConvertMetricKind(inner) => Transform::ConvertMetricKind(inner.build()?),
             Self::Filter(inner) => Transform::Filter(inner.build()?),
+            Self::ParseTimestamp(inner) => Transform::ParseTimestamp(inner.build()?),
             Self::RegexParser(inner) => Transform::RegexParser(inner.build()?),
             Self::RemoveFields(inner) => Transform::RemoveFields(inner.build()?),
             Self::Route(inner) => Transform::Route(inner.build()?),
@@ -64,7 +90,9 @@ pub enum StartingError {}
 pub enum Transform {
     AddFields(self::add_fields::Transform),
     Broadcast(self::broadcast::Transform),
+    ConvertMetricKind(self::convert_metric_kind::Transform),
     Filter(self::filter::Transform),
+    ParseTimestamp(self::parse_timestamp::Transform),
     RegexParser(self::regex_parser::Transform),
     RemoveFields(self::remove_fields::Transform),
     Route(self::route::Transform),
@@ -75,7 +103,9 @@ impl Transform {
         match self {
             Self::AddFields(inner) => inner.flavor(),
             Self::Broadcast(inner) => inner.flavor(),
+            Self::ConvertMetricKind(inner) => inner.flavor(),
             Self::Filter(inner) => inner.flavor(),
+            Self::ParseTimestamp(inner) => inner.flavor(),
             Self::RegexParser(inner) => inner.flavor(),
             Self::RemoveFields(inner) => inner.flavor(),
             Self::Route(inner) => inner.flavor(),
@@ -87,9 +117,17 @@ impl Transform {
         name: &ComponentName,
         receiver: Receiver,
         collector: Collector,
-    ) -> Result<tokio::task::JoinHandle<()>, StartingError> {
+    ) -> Result<tokio::task::JoinHandle<Result<(), ExecutionError>>, StartingError> {
+        let metrics = crate::components::metrics::register(
+            name,
+            COMPONENT_KIND,
+            self.flavor(),
+            Some(receiver.probe()),
+        );
+        let receiver = receiver.with_metrics(metrics.clone());
+        let collector = collector.with_metrics(metrics).with_transform(name);
         let span = tracing::info_span!(
-            "component",
+            COMPONENT_SPAN,
             name = name.as_ref(),
             kind = COMPONENT_KIND,
             flavor = self.flavor(),
@@ -97,7 +135,9 @@ impl Transform {
         Ok(match self {
             Self::AddFields(inner) => run(inner, span, receiver, collector).await?,
             Self::Broadcast(inner) => run(inner, span, receiver, collector).await?,
+            Self::ConvertMetricKind(inner) => run(inner, span, receiver, collector).await?,
             Self::Filter(inner) => run(inner, span, receiver, collector).await?,
+            Self::ParseTimestamp(inner) => run(inner, span, receiver, collector).await?,
             Self::RegexParser(inner) => run(inner, span, receiver, collector).await?,
             Self::RemoveFields(inner) => run(inner, span, receiver, collector).await?,
             Self::Route(inner) => run(inner, span, receiver, collector).await?,
@@ -126,19 +166,23 @@ trait Executable: Sized {
         self,
         mut receiver: Receiver,
         collector: Collector,
-    ) -> impl std::future::Future<Output = ()> + Send
+    ) -> impl std::future::Future<Output = Result<(), ExecutionError>> + Send
     where
         Self: Send + Sync,
     {
         async move {
             tracing::info!("starting");
-            while let Some(event) = receiver.recv().await {
+            let result = loop {
+                let Some(event) = receiver.recv().await else {
+                    break Ok(());
+                };
                 if let Err(err) = self.handle(&collector, event).await {
                     tracing::error!("unable to route event: {err:?}");
-                    break;
+                    break Err(err.into());
                 }
-            }
+            };
             tracing::info!("stopping");
+            result
         }
     }
 }
@@ -148,7 +192,7 @@ async fn run<E: Executable + Send + Sized + Sync + 'static>(
     span: tracing::Span,
     receiver: Receiver,
     collector: Collector,
-) -> Result<tokio::task::JoinHandle<()>, StartingError> {
+) -> Result<tokio::task::JoinHandle<Result<(), ExecutionError>>, StartingError> {
     use tracing::Instrument;
 
     Ok(tokio::spawn(async move {
@@ -0,0 +1,109 @@
+use crate::components::output::ComponentWithOutputs;
+use crate::event::log::EventLog;
+use crate::event::Event;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BuildError {}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+pub struct Config {
+    /// Text attribute containing the timestamp
+    field: String,
+    /// strftime format of the attribute, parsed as ISO8601 when not specified
+    #[serde(default)]
+    format: Option<String>,
+    /// Keeps the attribute once parsed
+    #[serde(default)]
+    keep_field: bool,
+}
+
+impl ComponentWithOutputs for Config {}
+
+impl Config {
+    pub fn build(self) -> Result<Transform, BuildError> {
+        Ok(Transform {
+            field: self.field,
+            format: self.format,
+            keep_field: self.keep_field,
+        })
+    }
+}
+
+pub struct Transform {
+    field: String,
+    format: Option<String>,
+    keep_field: bool,
+}
+
+impl Transform {
+    pub(crate) fn flavor(&self) -> &'static str {
+        "parse_timestamp"
+    }
+
+    fn handle_log(&self, mut event_log: EventLog) -> EventLog {
+        let Some(value) = event_log
+            .attributes
+            .get(self.field.as_str())
+            .and_then(|value| value.as_text())
+        else {
+            return event_log;
+        };
+        match crate::event::timestamp::parse(value, self.format.as_deref()) {
+            Some(timestamp) => {
+                event_log.timestamp = Some(timestamp);
+                if !self.keep_field {
+                    event_log.attributes.shift_remove(self.field.as_str());
+                }
+            }
+            None => tracing::debug!("unable to parse timestamp {value:?}"),
+        }
+        event_log
+    }
+}
+
+impl super::Executable for Transform {
+    fn transform(&self, event: Event) -> Event {
+        match event {
+            Event::Log(inner) => Event::Log(self.handle_log(inner)),
+            Event::Metric(inner) => Event::Metric(inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::log::EventLog;
+    use crate::transforms::Executable;
+
+    #[test]
+    fn should_parse_timestamp_attribute() {
+        let transform = super::Config {
+            field: "time".into(),
+            format: Some("%d/%b/%Y:%H:%M:%S %z".into()),
+            keep_field: false,
+        }
+        .build()
+        .unwrap();
+
+        let event = transform.transform(
+            EventLog::new("hello")
+                .with_attribute("time", "17/Oct/2024:07:56:51 +0200")
+                .into(),
+        );
+        let event = event.into_event_log().unwrap();
+        assert_eq!(
+            event.timestamp,
+            Some("2024-10-17T05:56:51Z".parse().unwrap())
+        );
+        assert!(!event.attributes.contains_key("time"));
+
+        let event = transform.transform(
+            EventLog::new("hello")
+                .with_attribute("time", "yesterday")
+                .into(),
+        );
+        let event = event.into_event_log().unwrap();
+        assert!(event.timestamp.is_none());
+        assert_eq!(event.attributes["time"].as_text(), Some("yesterday"));
+    }
+}
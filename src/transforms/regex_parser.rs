@@ -12,7 +12,7 @@ pub enum BuildError {
     ),
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 #[cfg_attr(test, derive(Default))]
 pub struct Config {
     pattern: String,
@@ -41,6 +41,8 @@ impl Transform {
         let EventLog {
             mut attributes,
             message,
+            timestamp,
+            metadata,
         } = event_log;
         let mut new_message = None::<String>;
         if let Some(capture) = self.pattern.captures(&message) {
@@ -58,11 +60,15 @@ impl Transform {
             EventLog {
                 attributes,
                 message: new_message.unwrap_or(message),
+                timestamp,
+                metadata,
             }
         } else {
             EventLog {
                 attributes,
                 message,
+                timestamp,
+                metadata,
             }
         }
     }
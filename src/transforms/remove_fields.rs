@@ -1,13 +1,22 @@
 use indexmap::IndexSet;
 
 use crate::components::output::ComponentWithOutputs;
+use crate::event::path::{AttributePath, PathError};
 use crate::event::Event;
 
 #[derive(Debug, thiserror::Error)]
-pub enum BuildError {}
+pub enum BuildError {
+    #[error("invalid field path")]
+    InvalidPath(
+        #[from]
+        #[source]
+        PathError,
+    ),
+}
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 pub struct Config {
+    /// Paths for the logs, like `http.request.headers[0]`, and tags for the metrics
     fields: IndexSet<String>,
 }
 
@@ -15,13 +24,20 @@ impl ComponentWithOutputs for Config {}
 
 impl Config {
     pub fn build(self) -> Result<Transform, BuildError> {
+        let paths = self
+            .fields
+            .iter()
+            .map(|name| name.parse())
+            .collect::<Result<_, _>>()?;
         Ok(Transform {
+            paths,
             fields: self.fields,
         })
     }
 }
 
 pub struct Transform {
+    paths: Vec<AttributePath>,
     fields: IndexSet<String>,
 }
 
@@ -35,18 +51,51 @@ impl super::Executable for Transform {
     fn transform(&self, event: Event) -> Event {
         match event {
             Event::Log(mut inner) => {
-                inner
-                    .attributes
-                    .retain(|key, _| !self.fields.contains(key.as_ref()));
+                for path in self.paths.iter() {
+                    inner.remove_path(path);
+                }
                 Event::Log(inner)
             }
             Event::Metric(mut inner) => {
                 inner
-                    .header
-                    .tags
+                    .tags_mut()
                     .retain(|key, _| !self.fields.contains(key.as_ref()));
                 Event::Metric(inner)
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::event::log::EventLog;
+    use crate::event::metric::{EventMetric, EventMetricValue};
+    use crate::transforms::Executable;
+
+    #[test]
+    fn should_remove_nested_fields() {
+        let config: super::Config =
+            toml::from_str(r#"fields = ["http.request.headers[0]", "service", "host.name"]"#)
+                .unwrap();
+        let transform = config.build().unwrap();
+
+        let event: EventLog = serde_json::from_str(
+            r#"{"message": "hello", "service": "api", "host.name": "a", "http": {"request": {"headers": ["accept", "host"]}}}"#,
+        )
+        .unwrap();
+        let event = transform.transform(event.into()).into_event_log().unwrap();
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({"message": "hello", "http": {"request": {"headers": ["host"]}}})
+        );
+
+        let event = EventMetric::new(0, "foo", "bar", EventMetricValue::Gauge(1.0))
+            .with_tag("service", "api")
+            .with_tag("host", "fake-server");
+        let event = transform
+            .transform(event.into())
+            .into_event_metric()
+            .unwrap();
+        assert_eq!(event.header.tags.len(), 1);
+    }
+}
@@ -19,7 +19,7 @@ fn default_fallback() -> NamedOutput {
     NamedOutput::Named("dropped".into())
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 #[cfg_attr(test, derive(Default))]
 pub struct Config {
     routes: IndexMap<NamedOutput, crate::transforms::condition::Config>,